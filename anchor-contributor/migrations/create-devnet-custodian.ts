@@ -5,6 +5,11 @@ import { connectToContributorProgram, readJson, readKeypair } from "./utils";
 
 const CORE_BRIDGE_ADDRESS = new web3.PublicKey(process.env.CORE_BRIDGE_ADDRESS);
 const TOKEN_BRIDGE_ADDRESS = new web3.PublicKey(process.env.TOKEN_BRIDGE_ADDRESS);
+const CONDUCTOR_CHAIN = parseInt(process.env.CONDUCTOR_CHAIN);
+const CONDUCTOR_ADDRESS = process.env.CONDUCTOR_ADDRESS;
+const CONDUCTOR_CONSISTENCY_LEVEL = parseInt(process.env.CONDUCTOR_CONSISTENCY_LEVEL);
+const GOVERNANCE_CHAIN = parseInt(process.env.GOVERNANCE_CHAIN);
+const GOVERNANCE_ADDRESS = process.env.GOVERNANCE_ADDRESS;
 
 async function main() {
   const rpc = "https://api.devnet.solana.com";
@@ -15,6 +20,8 @@ async function main() {
 
   console.log("wormhole", CORE_BRIDGE_ADDRESS.toString());
   console.log("token bridge", TOKEN_BRIDGE_ADDRESS.toString());
+  console.log("conductor", CONDUCTOR_CHAIN, CONDUCTOR_ADDRESS, CONDUCTOR_CONSISTENCY_LEVEL);
+  console.log("governance", GOVERNANCE_CHAIN, GOVERNANCE_ADDRESS);
   console.log("program id", programId.toString());
   console.log("payer", payer.publicKey.toString());
 
@@ -24,7 +31,14 @@ async function main() {
   const contributor = new IccoContributor(program, CORE_BRIDGE_ADDRESS, TOKEN_BRIDGE_ADDRESS, postVaaSolanaWithRetry);
 
   try {
-    const tx = await contributor.createCustodian(payer);
+    const tx = await contributor.createCustodian(
+      payer,
+      CONDUCTOR_CHAIN,
+      CONDUCTOR_ADDRESS,
+      CONDUCTOR_CONSISTENCY_LEVEL,
+      GOVERNANCE_CHAIN,
+      GOVERNANCE_ADDRESS
+    );
     console.log("tx", tx);
   } catch (e) {
     const custodianAccountInfo = await connection
@@ -2,6 +2,12 @@
 pub const SEED_PREFIX_CUSTODIAN: &str = "icco-custodian";
 pub const SEED_PREFIX_SALE: &str = "icco-sale";
 pub const SEED_PREFIX_BUYER: &str = "icco-buyer";
+pub const SEED_PREFIX_CLAIM: &str = "icco-claim";
+pub const SEED_PREFIX_UPGRADE_AUTHORITY: &str = "icco-upgrade";
+pub const SEED_PREFIX_UNWRAP: &str = "icco-unwrap";
+
+// wormhole
+pub const SEED_PREFIX_POSTED_VAA: &str = "PostedVAA";
 
 pub const CHAIN_ID: u16 = 1;
 
@@ -12,6 +18,14 @@ pub const PAYLOAD_SALE_SEALED: u8 = 3;
 pub const PAYLOAD_SALE_ABORTED: u8 = 4;
 pub const PAYLOAD_KYC_AUTHORITY_UPDATED: u8 = 6;
 
+// governance (module name is left-zero-padded to 32 bytes)
+pub const GOVERNANCE_MODULE: &[u8] = b"TokenSaleContributor";
+pub const GOVERNANCE_ACTION_UPGRADE_CONTRACT: u8 = 1;
+pub const GOVERNANCE_ACTION_UPDATE_CONDUCTOR: u8 = 2;
+pub const INDEX_GOVERNANCE_ACTION: usize = 32;
+pub const INDEX_GOVERNANCE_CHAIN: usize = 33;
+pub const INDEX_GOVERNANCE_PAYLOAD: usize = 35;
+
 // universal
 pub const PAYLOAD_HEADER_LEN: usize = 33; // payload + sale id
 pub const INDEX_SALE_ID: usize = 1;
@@ -25,8 +39,13 @@ pub const INDEX_SALE_INIT_SALE_END: usize = 100;
 pub const INDEX_SALE_INIT_ACCEPTED_TOKENS_START: usize = 132;
 pub const SALE_INIT_TAIL: usize = 84;
 
+// Claiming refunds or excesses passes two remaining accounts per accepted token.
+// With a fee payer other than the buyer, such a transaction takes two signatures
+// and 9 + 2n account keys, which is 472 + 66n bytes. Anything above 11 tokens
+// would not fit in a 1232-byte transaction.
+pub const ACCEPTED_TOKENS_MAX: usize = 11;
+
 pub const ACCEPTED_TOKEN_NUM_BYTES: usize = 33;
-pub const ACCEPTED_TOKENS_MAX: usize = 8;
 pub const INDEX_ACCEPTED_TOKEN_INDEX: usize = 0;
 pub const INDEX_ACCEPTED_TOKEN_ADDRESS: usize = 1;
 pub const INDEX_ACCEPTED_TOKEN_END: usize = 33;
@@ -10,13 +10,19 @@ use anchor_spl::{
 use crate::{
     constants::*,
     error::ContributorError,
-    state::{Buyer, Custodian, Sale},
+    state::{Buyer, Claim, Custodian, Sale},
 };
 
 /// Context allows contract owner to create an account that acts
-/// to hold all associated token accounts for all sales.
+/// to hold all associated token accounts for all sales. Only the
+/// program's upgrade authority can do this, because the custodian
+/// decides which conductor and Wormhole programs are trusted.
 /// See `create_custodian` instruction in lib.rs.
 ///
+/// Immutable
+/// * `program`
+/// * `program_data`
+///
 /// Mutable
 /// * `custodian`
 /// * `payer` (signer)
@@ -33,6 +39,16 @@ pub struct CreateCustodian<'info> {
     )]
     pub custodian: Account<'info, Custodian>,
 
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ ContributorError::InvalidAccount
+    )]
+    pub program: Program<'info, crate::program::AnchorContributor>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ContributorError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -40,15 +56,22 @@ pub struct CreateCustodian<'info> {
 
 /// Context provides all accounts required for someone to initialize a sale
 /// with a signed VAA sent by the conductor. A `Sale` is created at this step,
-/// which will be used for future actions.
+/// which will be used for future actions. A `Claim` is created for the VAA so
+/// it cannot be submitted again.
 /// See `init_sale` instruction in lib.rs.
 ///
+/// NOTE: With `init_sale`, remaining accounts are passed in for the first
+/// accepted token mints. The rest can be added with `add_accepted_tokens`.
+/// The Sale account is created without room for any accepted tokens and
+/// grows as they are added.
+///
 /// /// Immutable
 /// * `custodian`
 /// * `core_bridge_vaa`
 /// * `sale_token_mint`
 ///
 /// Mutable
+/// * `claim`
 /// * `sale`
 /// * `payer` (signer)
 #[derive(Accounts)]
@@ -61,6 +84,21 @@ pub struct InitSale<'info> {
     )]
     pub custodian: Account<'info, Custodian>,
 
+    #[account(
+        init_if_needed,
+        seeds = [
+            SEED_PREFIX_CLAIM.as_bytes(),
+            &Claim::get_emitter_address_from_vaa(&core_bridge_vaa)?,
+            &Claim::get_emitter_chain_from_vaa(&core_bridge_vaa)?,
+            &Claim::get_sequence_from_vaa(&core_bridge_vaa)?,
+        ],
+        payer = payer,
+        bump,
+        space = 8 + Claim::MAXIMUM_SIZE,
+        constraint = !claim.claimed @ ContributorError::VaaAlreadyClaimed,
+    )]
+    pub claim: Account<'info, Claim>,
+
     #[account(
         init,
         seeds = [
@@ -69,12 +107,12 @@ pub struct InitSale<'info> {
         ],
         payer = payer,
         bump,
-        space = 8 + Sale::MAXIMUM_SIZE
+        space = 8 + Sale::get_size(0)
     )]
     pub sale: Account<'info, Sale>,
 
     #[account(
-        constraint = core_bridge_vaa.owner.key() ==  Custodian::wormhole()? @ ContributorError::InvalidWormholeMessageAccount
+        constraint = core_bridge_vaa.owner.key() == custodian.wormhole @ ContributorError::InvalidWormholeMessageAccount
     )]
     /// CHECK: Posted VAA Message Data
     pub core_bridge_vaa: AccountInfo<'info>,
@@ -87,7 +125,7 @@ pub struct InitSale<'info> {
     pub sale_token_mint: AccountInfo<'info>,
 
     #[account(
-        constraint = token_bridge.key() == Custodian::token_bridge()? @ ContributorError::InvalidTokenBridgeProgram
+        constraint = token_bridge.key() == custodian.token_bridge @ ContributorError::InvalidTokenBridgeProgram
     )]
     /// CHECK: Token Bridge Program
     pub token_bridge: AccountInfo<'info>,
@@ -97,12 +135,62 @@ pub struct InitSale<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Context provides all accounts required for someone to add the accepted
+/// tokens of a sale that did not fit in the `init_sale` transaction. The same
+/// signed VAA used to initialize the sale is passed in again.
+/// See `add_accepted_tokens` instruction in lib.rs.
+///
+/// Immutable
+/// * `custodian`
+/// * `core_bridge_vaa`
+///
+/// Mutable
+/// * `sale`
+/// * `payer` (signer)
+///
+/// NOTE: With `add_accepted_tokens`, remaining accounts are passed in
+/// for the next accepted token mints.
+#[derive(Accounts)]
+pub struct AddAcceptedTokens<'info> {
+    #[account(
+        seeds = [
+            SEED_PREFIX_CUSTODIAN.as_bytes(),
+        ],
+        bump,
+    )]
+    pub custodian: Account<'info, Custodian>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEED_PREFIX_SALE.as_bytes(),
+            &sale.id,
+        ],
+        bump,
+    )]
+    pub sale: Account<'info, Sale>,
+
+    #[account(
+        constraint = core_bridge_vaa.owner.key() == custodian.wormhole @ ContributorError::InvalidWormholeMessageAccount
+    )]
+    /// CHECK: Posted VAA Message Data
+    pub core_bridge_vaa: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 /// Context provides all accounts required for user to send contribution
-/// to ongoing sale.
+/// to ongoing sale. The `owner` funds the contribution, but the `buyer`
+/// account belongs to the `beneficiary`, who is the one verified by the KYC
+/// authority and who claims allocations, excesses or refunds later. When
+/// contributing for yourself, `beneficiary` is the same as `owner`.
 /// See `contribute` instruction in lib.rs.
 ///
 /// Immutable
 /// * `custodian`
+/// * `beneficiary`
 ///
 /// Mutable
 /// * `sale`
@@ -135,23 +223,26 @@ pub struct Contribute<'info> {
         seeds = [
             SEED_PREFIX_BUYER.as_bytes(),
             &sale.id,
-            &owner.key().as_ref(),
+            &beneficiary.key().as_ref(),
         ],
         payer = owner,
         bump,
-        space = 8 + Buyer::MAXIMUM_SIZE,
+        space = 8 + Buyer::get_size(sale.totals.len()),
     )]
     pub buyer: Account<'info, Buyer>,
 
+    /// CHECK: Any public key can benefit from a contribution. The KYC
+    /// signature in the `contribute` instruction must be made out to it.
+    pub beneficiary: AccountInfo<'info>,
+
     pub accepted_mint: Account<'info, Mint>,
 
-    #[account(
-        mut,
-        associated_token::mint = accepted_mint,
-        associated_token::authority = owner,
-    )]
-    /// This must be an associated token account
-    pub buyer_token_acct: Account<'info, TokenAccount>,
+    #[account(mut)]
+    /// CHECK: This must be the owner's associated token account, which is
+    /// verified in the `contribute` instruction. If the accepted token is the
+    /// native mint, lamports are taken from `owner` instead and this account
+    /// is not used, so it does not need to exist.
+    pub buyer_token_acct: AccountInfo<'info>,
 
     #[account(
         init_if_needed,
@@ -179,6 +270,7 @@ pub struct Contribute<'info> {
 /// See `attest_contributions` instruction in lib.rs.
 ///
 /// Immutable
+/// * `custodian`
 /// * `sale`
 /// * `core_bridge`
 /// * `clock`
@@ -193,6 +285,14 @@ pub struct Contribute<'info> {
 /// * `payer` (signer)
 #[derive(Accounts)]
 pub struct AttestContributions<'info> {
+    #[account(
+        seeds = [
+            SEED_PREFIX_CUSTODIAN.as_bytes(),
+        ],
+        bump,
+    )]
+    pub custodian: Account<'info, Custodian>,
+
     #[account(
         seeds = [
             SEED_PREFIX_SALE.as_bytes(),
@@ -203,7 +303,7 @@ pub struct AttestContributions<'info> {
     pub sale: Account<'info, Sale>,
 
     #[account(
-        constraint = wormhole.key() == Custodian::wormhole()? @ ContributorError::InvalidWormholeProgram
+        constraint = wormhole.key() == custodian.wormhole @ ContributorError::InvalidWormholeProgram
     )]
     /// CHECK: Wormhole Program
     pub wormhole: AccountInfo<'info>,
@@ -212,7 +312,7 @@ pub struct AttestContributions<'info> {
         mut,
         seeds = [b"Bridge"],
         bump,
-        seeds::program =  Custodian::wormhole()?
+        seeds::program = custodian.wormhole
     )]
     /// CHECK: Wormhole Config
     pub wormhole_config: AccountInfo<'info>,
@@ -221,7 +321,7 @@ pub struct AttestContributions<'info> {
         mut,
         seeds = [b"fee_collector"],
         bump,
-        seeds::program = Custodian::wormhole()?
+        seeds::program = custodian.wormhole
     )]
     /// CHECK: Wormhole Fee Collector
     pub wormhole_fee_collector: AccountInfo<'info>,
@@ -241,7 +341,7 @@ pub struct AttestContributions<'info> {
             wormhole_emitter.key().as_ref()
         ],
         bump,
-        seeds::program = Custodian::wormhole()?
+        seeds::program = custodian.wormhole
     )]
     /// CHECK: Wormhole Sequence Number
     pub wormhole_sequence: AccountInfo<'info>,
@@ -340,7 +440,7 @@ pub struct BridgeSealedContribution<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 
     #[account(
-        constraint = token_bridge.key() == Custodian::token_bridge()? @ ContributorError::InvalidTokenBridgeProgram
+        constraint = token_bridge.key() == custodian.token_bridge @ ContributorError::InvalidTokenBridgeProgram
     )]
     /// CHECK: Token Bridge Program
     pub token_bridge: AccountInfo<'info>,
@@ -377,13 +477,13 @@ pub struct BridgeSealedContribution<'info> {
         mut,
         seeds = [b"config"],
         bump,
-        seeds::program = Custodian::token_bridge()?
+        seeds::program = custodian.token_bridge
     )]
     /// CHECK: Token Bridge Config
     pub token_bridge_config: AccountInfo<'info>,
 
     #[account(
-        constraint = wormhole.key() == Custodian::wormhole()? @ ContributorError::InvalidWormholeProgram
+        constraint = wormhole.key() == custodian.wormhole @ ContributorError::InvalidWormholeProgram
     )]
     /// CHECK: Wormhole Program
     pub wormhole: AccountInfo<'info>,
@@ -392,7 +492,7 @@ pub struct BridgeSealedContribution<'info> {
         mut,
         seeds = [b"Bridge"],
         bump,
-        seeds::program = Custodian::wormhole()?
+        seeds::program = custodian.wormhole
     )]
     /// CHECK: Wormhole Config
     pub wormhole_config: AccountInfo<'info>,
@@ -401,7 +501,7 @@ pub struct BridgeSealedContribution<'info> {
         mut,
         seeds = [b"fee_collector"],
         bump,
-        seeds::program = Custodian::wormhole()?
+        seeds::program = custodian.wormhole
     )]
     /// CHECK: Wormhole Fee Collector
     pub wormhole_fee_collector: AccountInfo<'info>,
@@ -410,7 +510,7 @@ pub struct BridgeSealedContribution<'info> {
         mut,
         seeds = [b"emitter"],
         bump,
-        seeds::program = Custodian::token_bridge()?
+        seeds::program = custodian.token_bridge
     )]
     /// CHECK: Wormhole Emitter is the Token Bridge Program
     pub wormhole_emitter: AccountInfo<'info>,
@@ -422,7 +522,7 @@ pub struct BridgeSealedContribution<'info> {
             wormhole_emitter.key().as_ref()
         ],
         bump,
-        seeds::program = Custodian::wormhole()?
+        seeds::program = custodian.wormhole
     )]
     /// CHECK: Wormhole Sequence Number
     pub wormhole_sequence: AccountInfo<'info>,
@@ -461,7 +561,9 @@ pub struct BridgeSealedContribution<'info> {
 /// * `core_bridge_vaa`
 ///
 /// Mutable
+/// * `claim`
 /// * `sale`
+/// * `payer` (signer)
 #[derive(Accounts)]
 pub struct AbortSale<'info> {
     #[account(
@@ -472,6 +574,21 @@ pub struct AbortSale<'info> {
     )]
     pub custodian: Account<'info, Custodian>,
 
+    #[account(
+        init_if_needed,
+        seeds = [
+            SEED_PREFIX_CLAIM.as_bytes(),
+            &Claim::get_emitter_address_from_vaa(&core_bridge_vaa)?,
+            &Claim::get_emitter_chain_from_vaa(&core_bridge_vaa)?,
+            &Claim::get_sequence_from_vaa(&core_bridge_vaa)?,
+        ],
+        payer = payer,
+        bump,
+        space = 8 + Claim::MAXIMUM_SIZE,
+        constraint = !claim.claimed @ ContributorError::VaaAlreadyClaimed,
+    )]
+    pub claim: Account<'info, Claim>,
+
     #[account(
         mut,
         seeds = [
@@ -483,11 +600,13 @@ pub struct AbortSale<'info> {
     pub sale: Account<'info, Sale>,
 
     #[account(
-        constraint = core_bridge_vaa.owner.key() == Custodian::wormhole()? @ ContributorError::InvalidWormholeMessageAccount
+        constraint = core_bridge_vaa.owner.key() == custodian.wormhole @ ContributorError::InvalidWormholeMessageAccount
     )]
     /// CHECK: Posted VAA Message Data
     pub core_bridge_vaa: AccountInfo<'info>,
 
+    #[account(mut)]
+    pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
@@ -501,8 +620,9 @@ pub struct AbortSale<'info> {
 /// * `custodian_sale_token_acct`
 ///
 /// Mutable
+/// * `claim`
 /// * `sale`
-/// * `owner` (signer)
+/// * `payer` (signer)
 #[derive(Accounts)]
 pub struct SealSale<'info> {
     #[account(
@@ -513,6 +633,21 @@ pub struct SealSale<'info> {
     )]
     pub custodian: Account<'info, Custodian>,
 
+    #[account(
+        init_if_needed,
+        seeds = [
+            SEED_PREFIX_CLAIM.as_bytes(),
+            &Claim::get_emitter_address_from_vaa(&core_bridge_vaa)?,
+            &Claim::get_emitter_chain_from_vaa(&core_bridge_vaa)?,
+            &Claim::get_sequence_from_vaa(&core_bridge_vaa)?,
+        ],
+        payer = payer,
+        bump,
+        space = 8 + Claim::MAXIMUM_SIZE,
+        constraint = !claim.claimed @ ContributorError::VaaAlreadyClaimed,
+    )]
+    pub claim: Account<'info, Claim>,
+
     #[account(
         mut,
         seeds = [
@@ -524,7 +659,7 @@ pub struct SealSale<'info> {
     pub sale: Account<'info, Sale>,
 
     #[account(
-        constraint = core_bridge_vaa.owner.key() == Custodian::wormhole()? @ ContributorError::InvalidWormholeMessageAccount
+        constraint = core_bridge_vaa.owner.key() == custodian.wormhole @ ContributorError::InvalidWormholeMessageAccount
     )]
     /// CHECK: Posted VAA Message Data
     pub core_bridge_vaa: AccountInfo<'info>,
@@ -541,6 +676,8 @@ pub struct SealSale<'info> {
     /// continue.
     pub custodian_sale_token_acct: Account<'info, TokenAccount>,
 
+    #[account(mut)]
+    pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
@@ -629,6 +766,7 @@ pub struct ClaimAllocation<'info> {
 /// /// Immutable
 /// * `custodian`
 /// * `sale`
+/// * `native_mint`
 ///
 /// Mutable
 /// * `buyer`
@@ -666,6 +804,11 @@ pub struct ClaimRefunds<'info> {
     )]
     pub buyer: Account<'info, Buyer>,
 
+    #[account(address = spl_token::native_mint::id())]
+    /// CHECK: Wrapped SOL is paid out through a temporary token account, which
+    /// needs the native mint to be initialized.
+    pub native_mint: AccountInfo<'info>,
+
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -679,6 +822,7 @@ pub struct ClaimRefunds<'info> {
 /// /// Immutable
 /// * `custodian`
 /// * `sale`
+/// * `native_mint`
 ///
 /// Mutable
 /// * `buyer`
@@ -716,6 +860,11 @@ pub struct ClaimExcesses<'info> {
     )]
     pub buyer: Account<'info, Buyer>,
 
+    #[account(address = spl_token::native_mint::id())]
+    /// CHECK: Wrapped SOL is paid out through a temporary token account, which
+    /// needs the native mint to be initialized.
+    pub native_mint: AccountInfo<'info>,
+
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -732,7 +881,9 @@ pub struct ClaimExcesses<'info> {
 /// * `core_bridge_vaa`
 ///
 /// Mutable
+/// * `claim`
 /// * `sale`
+/// * `payer` (signer)
 #[derive(Accounts)]
 pub struct UpdateKycAuthority<'info> {
     #[account(
@@ -743,6 +894,21 @@ pub struct UpdateKycAuthority<'info> {
     )]
     pub custodian: Account<'info, Custodian>,
 
+    #[account(
+        init_if_needed,
+        seeds = [
+            SEED_PREFIX_CLAIM.as_bytes(),
+            &Claim::get_emitter_address_from_vaa(&core_bridge_vaa)?,
+            &Claim::get_emitter_chain_from_vaa(&core_bridge_vaa)?,
+            &Claim::get_sequence_from_vaa(&core_bridge_vaa)?,
+        ],
+        payer = payer,
+        bump,
+        space = 8 + Claim::MAXIMUM_SIZE,
+        constraint = !claim.claimed @ ContributorError::VaaAlreadyClaimed,
+    )]
+    pub claim: Account<'info, Claim>,
+
     #[account(
         mut,
         seeds = [
@@ -754,10 +920,65 @@ pub struct UpdateKycAuthority<'info> {
     pub sale: Account<'info, Sale>,
 
     #[account(
-        constraint = core_bridge_vaa.owner.key() == Custodian::wormhole()? @ ContributorError::InvalidWormholeMessageAccount
+        constraint = core_bridge_vaa.owner.key() == custodian.wormhole @ ContributorError::InvalidWormholeMessageAccount
+    )]
+    /// CHECK: Posted VAA Message Data
+    pub core_bridge_vaa: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Context provides all accounts required for someone to submit a signed
+/// VAA sent by the governance emitter. A `Claim` is created for the VAA so
+/// it cannot be submitted again.
+/// See `submit_governance` instruction in lib.rs.
+///
+/// Immutable
+/// * `core_bridge_vaa`
+///
+/// Mutable
+/// * `custodian`
+/// * `claim`
+/// * `payer` (signer)
+///
+/// NOTE: With `submit_governance`, an upgrade needs the program data,
+/// program, buffer, upgrade authority, rent, clock and BPF upgradeable
+/// loader accounts passed in as remaining accounts (in that order).
+#[derive(Accounts)]
+pub struct SubmitGovernance<'info> {
+    #[account(
+        mut,
+        seeds = [
+            SEED_PREFIX_CUSTODIAN.as_bytes(),
+        ],
+        bump,
+    )]
+    pub custodian: Account<'info, Custodian>,
+
+    #[account(
+        init_if_needed,
+        seeds = [
+            SEED_PREFIX_CLAIM.as_bytes(),
+            &Claim::get_emitter_address_from_vaa(&core_bridge_vaa)?,
+            &Claim::get_emitter_chain_from_vaa(&core_bridge_vaa)?,
+            &Claim::get_sequence_from_vaa(&core_bridge_vaa)?,
+        ],
+        payer = payer,
+        bump,
+        space = 8 + Claim::MAXIMUM_SIZE,
+        constraint = !claim.claimed @ ContributorError::VaaAlreadyClaimed,
+    )]
+    pub claim: Account<'info, Claim>,
+
+    #[account(
+        constraint = core_bridge_vaa.owner.key() == custodian.wormhole @ ContributorError::InvalidWormholeMessageAccount
     )]
     /// CHECK: Posted VAA Message Data
     pub core_bridge_vaa: AccountInfo<'info>,
 
+    #[account(mut)]
+    pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
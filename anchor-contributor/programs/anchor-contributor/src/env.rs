@@ -1,4 +0,0 @@
-pub const CONDUCTOR_CHAIN: &str = std::env!("CONDUCTOR_CHAIN");
-pub const CONDUCTOR_ADDRESS: &str = std::env!("CONDUCTOR_ADDRESS");
-pub const CORE_BRIDGE_ADDRESS: &str = std::env!("CORE_BRIDGE_ADDRESS");
-pub const TOKEN_BRIDGE_ADDRESS: &str = std::env!("TOKEN_BRIDGE_ADDRESS");
@@ -127,4 +127,34 @@ pub enum ContributorError {
 
     #[msg("InvalidSaleToken")]
     InvalidSaleToken,
+
+    #[msg("NotUpgradeAuthority")]
+    NotUpgradeAuthority,
+
+    #[msg("InvalidGovernanceChain")]
+    InvalidGovernanceChain,
+
+    #[msg("InvalidGovernanceAddress")]
+    InvalidGovernanceAddress,
+
+    #[msg("InvalidGovernanceModule")]
+    InvalidGovernanceModule,
+
+    #[msg("InvalidGovernanceTargetChain")]
+    InvalidGovernanceTargetChain,
+
+    #[msg("InvalidUpgradeBuffer")]
+    InvalidUpgradeBuffer,
+
+    #[msg("VaaAlreadyClaimed")]
+    VaaAlreadyClaimed,
+
+    #[msg("InvalidConsistencyLevel")]
+    InvalidConsistencyLevel,
+
+    #[msg("SaleNotInitialized")]
+    SaleNotInitialized,
+
+    #[msg("InvalidSaleInitVaa")]
+    InvalidSaleInitVaa,
 }
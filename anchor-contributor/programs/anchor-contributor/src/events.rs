@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+
+/// Emitted by `init_sale` once the Sale account is created. If the sale token
+/// could not be verified, `contributions_blocked` is true and `sale_token_mint`
+/// is zeroed out.
+#[event]
+pub struct SaleInitialized {
+    pub sale_id: [u8; 32],
+    pub token_chain: u16,
+    pub token_address: [u8; 32],
+    pub sale_token_mint: Pubkey,
+    pub num_accepted: u8,
+    pub start: u64,
+    pub end: u64,
+    pub unlock_allocation: u64,
+    pub contributions_blocked: bool,
+}
+
+/// Emitted by `contribute`. `total` is the buyer's running contribution for
+/// this token index, including `amount`. `funder` differs from `buyer` when
+/// someone contributes on the buyer's behalf.
+#[event]
+pub struct Contributed {
+    pub sale_id: [u8; 32],
+    pub buyer: Pubkey,
+    pub funder: Pubkey,
+    pub token_index: u8,
+    pub amount: u64,
+    pub total: u64,
+}
+
+/// Emitted by `attest_contributions` after the contributions are posted to
+/// Wormhole.
+#[event]
+pub struct ContributionsAttested {
+    pub sale_id: [u8; 32],
+    pub wormhole_message: Pubkey,
+}
+
+/// Emitted by `seal_sale`.
+#[event]
+pub struct SaleSealed {
+    pub sale_id: [u8; 32],
+}
+
+/// Emitted by `abort_sale`.
+#[event]
+pub struct SaleAborted {
+    pub sale_id: [u8; 32],
+}
+
+/// Emitted by `bridge_sealed_contribution`. `amount` can be zero when there
+/// was nothing left to transfer after excess contributions.
+#[event]
+pub struct ContributionBridged {
+    pub sale_id: [u8; 32],
+    pub token_index: u8,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted by `claim_allocation`.
+#[event]
+pub struct AllocationClaimed {
+    pub sale_id: [u8; 32],
+    pub buyer: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted by `claim_excesses`, once per token index with a non-zero excess.
+#[event]
+pub struct ExcessClaimed {
+    pub sale_id: [u8; 32],
+    pub buyer: Pubkey,
+    pub token_index: u8,
+    pub amount: u64,
+}
+
+/// Emitted by `claim_refunds`, once per token index with a non-zero refund.
+#[event]
+pub struct RefundClaimed {
+    pub sale_id: [u8; 32],
+    pub buyer: Pubkey,
+    pub token_index: u8,
+    pub amount: u64,
+}
+
+/// Emitted by `update_kyc_authority`.
+#[event]
+pub struct KycAuthorityUpdated {
+    pub sale_id: [u8; 32],
+    pub kyc_authority: [u8; 20],
+}
+
+/// Emitted by `submit_governance` when the conductor is replaced.
+#[event]
+pub struct ConductorUpdated {
+    pub conductor_chain: u16,
+    pub conductor_address: [u8; 32],
+}
+
+/// Emitted by `submit_governance` when the program is upgraded from `buffer`.
+#[event]
+pub struct ContractUpgraded {
+    pub buffer: Pubkey,
+}
@@ -3,8 +3,9 @@ use anchor_lang::prelude::*;
 mod constants;
 mod context;
 mod cryptography;
-mod env;
 mod error;
+mod events;
+mod native;
 mod state;
 mod token_bridge;
 mod wormhole;
@@ -12,6 +13,8 @@ mod wormhole;
 use constants::*;
 use context::*;
 use error::*;
+use events::*;
+use native::*;
 use token_bridge::*;
 use wormhole::*;
 
@@ -22,23 +25,55 @@ pub mod anchor_contributor {
     use super::*;
     use anchor_lang::solana_program::{
         borsh::try_from_slice_unchecked,
+        bpf_loader_upgradeable,
         instruction::Instruction,
         program::{invoke, invoke_signed},
         program_option::COption,
         system_instruction::transfer,
         sysvar::*,
     };
-    use anchor_spl::token;
+    use anchor_spl::{associated_token::get_associated_token_address, token};
 
     use itertools::izip;
-    use state::custodian::Custodian;
+    use state::{
+        custodian::{Custodian, CustodianConfig},
+        sale::Sale,
+    };
 
     /// Instruction to create the custodian account (which we referr to as `custodian`)
     /// in all instruction contexts found in contexts.rs.
-    pub fn create_custodian(ctx: Context<CreateCustodian>) -> Result<()> {
+    ///
+    /// The custodian stores the conductor's chain and address, as well as the Wormhole
+    /// core bridge and token bridge program IDs. Every instruction context reads these
+    /// from the custodian, so the same program build can be deployed to any cluster.
+    /// Conductor VAAs are only accepted if their consistency level is at least
+    /// `conductor_consistency_level`, which is interpreted by the conductor's chain.
+    /// The governance emitter's chain and address are stored, too, so VAAs it emits
+    /// can be submitted with `submit_governance`.
+    pub fn create_custodian(
+        ctx: Context<CreateCustodian>,
+        conductor_chain: u16,
+        conductor_address: [u8; 32],
+        conductor_consistency_level: u8,
+        governance_chain: u16,
+        governance_address: [u8; 32],
+        wormhole: Pubkey,
+        token_bridge: Pubkey,
+    ) -> Result<()> {
         // We save the "owner" in the custodian account. But the custodian does not
         // need to be mutated in future interactions with the program.
-        ctx.accounts.custodian.new()?;
+        ctx.accounts.custodian.new(
+            &ctx.accounts.payer.key(),
+            CustodianConfig {
+                conductor_chain,
+                conductor_address,
+                conductor_consistency_level,
+                governance_chain,
+                governance_address,
+                wormhole,
+                token_bridge,
+            },
+        )?;
 
         Ok(())
     }
@@ -59,12 +94,17 @@ pub mod anchor_contributor {
             PAYLOAD_SALE_INIT_SOLANA,
         )?;
 
+        // Mark the VAA as consumed.
+        ctx.accounts.claim.claim()?;
+
         // Once verified, we deserialize the VAA payload to initialize the Sale
         // account with information relevant to perform future actions regarding
         // this particular sale. It uses a 32-byte ID generated from the VAA as its
         // identifier.
+        let payer = ctx.accounts.payer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
         let sale = &mut ctx.accounts.sale;
-        sale.parse_sale_init(&msg.payload)?;
+        sale.parse_sale_init(&msg)?;
 
         // Check that sale_token_mint is legitimate
         let mint_acct_info = &ctx.accounts.sale_token_mint;
@@ -147,48 +187,75 @@ pub mod anchor_contributor {
             sale.set_sale_token_mint_info(&mint, &mint_info, &ctx.accounts.custodian.key())?;
         }
 
-        // We need to verify that the accepted tokens are actual mints.
-        // We set status to invalid on bad ones.
-        let assets = &mut sale.totals;
+        // We need to verify that the accepted tokens are actual mints. We set status
+        // to invalid on bad ones. If there are too many accepted tokens to pass in as
+        // remaining accounts in one transaction, the rest are added with the
+        // `add_accepted_tokens` instruction.
         let accepted_mints = &ctx.remaining_accounts[..];
-        require!(
-            assets.len() == accepted_mints.len(),
-            ContributorError::InvalidRemainingAccounts
-        );
-        for (asset, accepted_mint_acct_info) in izip!(assets, accepted_mints) {
-            // If the remaining account does not match the key of the accepted asset's mint,
-            // throw because wrong account is passed into instruction.
-            require!(
-                accepted_mint_acct_info.key() == asset.mint,
-                ContributorError::InvalidRemainingAccounts,
-            );
+        Sale::grow_account(
+            &sale.to_account_info(),
+            &payer,
+            &system_program,
+            accepted_mints.len(),
+        )?;
+        sale.add_accepted_tokens(&msg, accepted_mints)?;
+
+        // Let indexers know about the new sale, including whether we had to
+        // block contributions because of a bad sale token.
+        emit!(SaleInitialized {
+            sale_id: sale.id,
+            token_chain: sale.token_chain,
+            token_address: sale.token_address,
+            sale_token_mint: sale.sale_token_mint,
+            num_accepted: sale.num_accepted,
+            start: sale.times.start,
+            end: sale.times.end,
+            unlock_allocation: sale.times.unlock_allocation,
+            contributions_blocked: sale.contributions_blocked,
+        });
 
-            // Check whether we should invalidate the accepted asset.
-            match *accepted_mint_acct_info.owner == token::ID {
-                false => {
-                    // If the remaining account is not owned by token program, it is invalid.
-                    asset.invalidate();
-                }
-                _ => {
-                    match accepted_mint_acct_info.try_borrow_data() {
-                        Err(_) => {
-                            // If the remaining account is not a real account, it is invalid.
-                            asset.invalidate();
-                        }
-                        Ok(data) => {
-                            // If the remaining account does not deserialize to Mint account, it is invalid.
-                            let mut bf: &[u8] = &data;
-                            if token::Mint::try_deserialize(&mut bf).is_err() {
-                                asset.invalidate();
-                            }
-                        }
-                    }
-                }
-            };
-        }
+        // Finish instruction.
+        Ok(())
+    }
+
+    /// Instruction to add accepted tokens to a sale that has not been fully initialized
+    /// by `init_sale`. This parses the same signed VAA sent by the conductor to initialize
+    /// the sale.
+    ///
+    /// Each accepted token needs its mint passed in as a remaining account, which limits
+    /// how many can be verified in one transaction. The mints passed in here are the next
+    /// ones in the order found in the VAA. Once every accepted token is added, the sale is
+    /// initialized and users can contribute.
+    ///
+    /// The Sale account is grown with `realloc` to hold the new accepted tokens, and the
+    /// payer covers the additional rent.
+    pub fn add_accepted_tokens<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, AddAcceptedTokens<'info>>,
+    ) -> Result<()> {
+        // We verify that the signed VAA has the same sale information as the Sale
+        // account we pass into the context. It also needs to be emitted from the
+        // conductor we know.
+        let msg = ctx
+            .accounts
+            .custodian
+            .parse_and_verify_conductor_vaa_and_sale(
+                &ctx.accounts.core_bridge_vaa,
+                PAYLOAD_SALE_INIT_SOLANA,
+                ctx.accounts.sale.id,
+            )?;
+
+        // Grow the Sale account to fit the new accepted tokens.
+        let accepted_mints = &ctx.remaining_accounts[..];
+        Sale::grow_account(
+            &ctx.accounts.sale.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sale.totals.len() + accepted_mints.len(),
+        )?;
 
-        // Write sale id in program log for reference.
-        msg!("sale: {}", hex::encode(&sale.id));
+        ctx.accounts
+            .sale
+            .add_accepted_tokens(&msg, accepted_mints)?;
 
         // Finish instruction.
         Ok(())
@@ -196,8 +263,12 @@ pub mod anchor_contributor {
 
     /// Instruction to contribute to an ongoing sale. The sale account needs to be mutable so we
     /// can uptick the total contributions for this sale. A buyer account will be created if it
-    /// hasn't been already from a previous contribution, seeded by the sale ID and the buyer's
-    /// public key.
+    /// hasn't been already from a previous contribution, seeded by the sale ID and the
+    /// beneficiary's public key.
+    ///
+    /// The owner of this instruction funds the contribution, but it is credited to the
+    /// beneficiary. This lets a custodial partner (like an exchange) contribute on behalf of
+    /// its users. Buyers contributing for themselves pass their own key as the beneficiary.
     ///
     /// As a part of this instruction, we need to verify that the contribution is allowed by checking
     /// a signature provided from an outside source (a know-your-customer entity).
@@ -205,18 +276,25 @@ pub mod anchor_contributor {
     /// Once everything is verified, the sale and buyer accounts are updated to reflect this
     /// contribution amount and the contribution will be transferred from the buyer's
     /// associated token account to the custodian's associated token account.
+    ///
+    /// If the accepted token is the native mint, the contribution is paid in lamports
+    /// from the owner and wrapped in the custodian's associated token account.
     pub fn contribute(ctx: Context<Contribute>, amount: u64, kyc_signature: Vec<u8>) -> Result<()> {
         // buyer's token account -> custodian's associated token account
         // These references will be used throughout the instruction
         let buyer_token_acct = &ctx.accounts.buyer_token_acct;
         let custodian_token_acct = &ctx.accounts.custodian_token_acct;
 
-        // We refer to the buyer (owner) of this instruction as the transfer_authority
-        // for the SPL transfer that will happen at the end of all the accounting processing.
+        // We refer to the owner of this instruction as the transfer_authority for the SPL
+        // transfer that will happen at the end of all the accounting processing. The
+        // beneficiary is who the contribution is credited to.
         let transfer_authority = &ctx.accounts.owner;
+        let beneficiary = &ctx.accounts.beneficiary;
 
-        // Check that sale on Solana is not blocked.
+        // Check that every accepted token has been added to the sale and that the
+        // sale on Solana is not blocked.
         let sale = &ctx.accounts.sale;
+        require!(sale.initialized, ContributorError::SaleNotInitialized);
         require!(
             !sale.is_blocked_contributions(),
             ContributorError::SaleContributionsAreBlocked
@@ -243,11 +321,13 @@ pub mod anchor_contributor {
 
         // We verify the KYC signature by encoding specific details of this contribution the
         // same way the KYC entity signed for the transaction. If we cannot recover the KYC's
-        // public key using ecdsa recovery, we cannot allow the contribution to continue.
+        // public key using ecdsa recovery, we cannot allow the contribution to continue. The
+        // KYC entity vets the beneficiary, not whoever is paying for the contribution.
         sale.verify_kyc_authority(
+            &ctx.accounts.custodian.conductor_address,
             asset.token_index,
             amount,
-            &transfer_authority.key(),
+            &beneficiary.key(),
             buyer.contributions[idx].amount,
             &kyc_signature,
         )?;
@@ -264,20 +344,57 @@ pub mod anchor_contributor {
         // And we do the same with the Buyer account.
         ctx.accounts.buyer.contribute(idx, amount)?;
 
-        // Finally transfer SPL tokens from the buyer's associated token account to the
-        // custodian's associated token account. Verify the custodian's associated
-        // token account
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
-                    from: buyer_token_acct.to_account_info(),
-                    to: custodian_token_acct.to_account_info(),
-                    authority: transfer_authority.to_account_info(),
-                },
-            ),
+        emit!(Contributed {
+            sale_id: ctx.accounts.sale.id,
+            buyer: beneficiary.key(),
+            funder: transfer_authority.key(),
+            token_index: ctx.accounts.sale.totals[idx].token_index,
             amount,
-        )?;
+            total: ctx.accounts.buyer.contributions[idx].amount,
+        });
+
+        // Finally transfer the contribution to the custodian's associated token account.
+        if ctx.accounts.accepted_mint.key() == spl_token::native_mint::id() {
+            // If the accepted token is wrapped SOL, we take lamports instead and wrap them
+            // in the custodian's associated token account. The buyer's associated token
+            // account is left untouched in this case.
+            invoke(
+                &transfer(
+                    &transfer_authority.key(),
+                    &custodian_token_acct.key(),
+                    amount,
+                ),
+                &ctx.accounts.to_account_infos(),
+            )?;
+            invoke(
+                &spl_token::instruction::sync_native(
+                    &spl_token::id(),
+                    &custodian_token_acct.key(),
+                )?,
+                &ctx.accounts.to_account_infos(),
+            )?;
+        } else {
+            // Otherwise transfer SPL tokens from the buyer's associated token account.
+            require!(
+                buyer_token_acct.key()
+                    == get_associated_token_address(
+                        &transfer_authority.key(),
+                        &ctx.accounts.accepted_mint.key()
+                    ),
+                ContributorError::InvalidAccount
+            );
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: buyer_token_acct.to_account_info(),
+                        to: custodian_token_acct.to_account_info(),
+                        authority: transfer_authority.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+        }
 
         // Finish instruction.
         Ok(())
@@ -350,6 +467,11 @@ pub mod anchor_contributor {
             ],
         )?;
 
+        emit!(ContributionsAttested {
+            sale_id: ctx.accounts.sale.id,
+            wormhole_message: ctx.accounts.wormhole_message.key(),
+        });
+
         // Finish instruction.
         Ok(())
     }
@@ -377,6 +499,9 @@ pub mod anchor_contributor {
             sale.id,
         )?;
 
+        // Mark the VAA as consumed.
+        ctx.accounts.claim.claim()?;
+
         // After verifying the VAA, save the allocation and excess contributions per
         // accepted asset. Change the state from Active to Sealed.
         sale.parse_sale_sealed(&msg.payload)?;
@@ -414,6 +539,8 @@ pub mod anchor_contributor {
             asset.prepare_for_transfer();
         }
 
+        emit!(SaleSealed { sale_id: sale.id });
+
         // Finish instruction.
         Ok(())
     }
@@ -474,7 +601,7 @@ pub mod anchor_contributor {
                 amount,
                 fee: 0,
                 target_address: sale.recipient,
-                target_chain: Custodian::conductor_chain()?,
+                target_chain: custodian.conductor_chain,
             };
 
             let token_bridge_key = &ctx.accounts.token_bridge.key();
@@ -589,7 +716,15 @@ pub mod anchor_contributor {
         }
 
         // Even if there is nothing to transfer, we will change the state.
-        ctx.accounts.sale.totals[idx].set_transferred();
+        let sale = &mut ctx.accounts.sale;
+        sale.totals[idx].set_transferred();
+
+        emit!(ContributionBridged {
+            sale_id: sale.id,
+            token_index: sale.totals[idx].token_index,
+            mint: *accepted_mint_key,
+            amount,
+        });
 
         // Finish instruction.
         Ok(())
@@ -616,8 +751,16 @@ pub mod anchor_contributor {
                 sale.id,
             )?;
 
-        // Finish the instruction by changing the status of the sale to Aborted.
-        sale.parse_sale_aborted(&msg.payload)
+        // Mark the VAA as consumed.
+        ctx.accounts.claim.claim()?;
+
+        // Change the status of the sale to Aborted.
+        sale.parse_sale_aborted(&msg.payload)?;
+
+        emit!(SaleAborted { sale_id: sale.id });
+
+        // Finish instruction.
+        Ok(())
     }
 
     /// Instruction to claim refunds from an aborted sale. Only the buyer account needs to
@@ -629,6 +772,10 @@ pub mod anchor_contributor {
     ///
     /// There are n transfers for the refunds, depending on however many tokens a user has
     /// contributed to the sale.
+    ///
+    /// Refunds of wrapped SOL are paid out in lamports. For the native mint, the buyer's
+    /// remaining account is the temporary token account derived from `owner` (see
+    /// `UnwrapNative`) instead of an associated token account.
     pub fn claim_refunds<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, ClaimRefunds<'info>>,
     ) -> Result<()> {
@@ -656,11 +803,6 @@ pub mod anchor_contributor {
         let owner = &ctx.accounts.owner;
         let transfer_authority = &ctx.accounts.custodian;
 
-        // This is used in case we need to use a native solana transfer.
-        //
-        // let mut all_accts = ctx.accounts.to_account_infos();
-        // all_accts.extend_from_slice(&ctx.remaining_accounts);
-
         // We will mutate the buyer's accounting and state for each contributed mint.
         let buyer = &mut ctx.accounts.buyer;
         for (idx, (asset, custodian_token_acct, buyer_token_acct)) in
@@ -673,38 +815,70 @@ pub mod anchor_contributor {
                 continue;
             }
 
-            // Verify remaining accounts are associated token accounts.
-            // Either both are valid or both are invalid. If only one
-            // is valid, then there is something wrong.
-            // In the case that both are invalid, this is when the accepted
-            // token itself is invalid.
-            match (
-                asset.deserialize_associated_token_account(
+            // Wrapped SOL is paid out in lamports through a temporary token account
+            // owned by the custodian, which is passed in place of the buyer's
+            // associated token account.
+            if asset.mint == spl_token::native_mint::id() {
+                require!(
+                    asset
+                        .deserialize_associated_token_account(
+                            custodian_token_acct,
+                            &ctx.accounts.custodian.key(),
+                        )?
+                        .is_some(),
+                    ContributorError::InvalidAccount
+                );
+                UnwrapNative {
+                    custodian: &transfer_authority.to_account_info(),
                     custodian_token_acct,
-                    &ctx.accounts.custodian.key(),
-                )?,
-                asset.deserialize_associated_token_account(buyer_token_acct, &owner.key())?,
-            ) {
-                (Some(_), Some(_)) => {
-                    token::transfer(
-                        CpiContext::new_with_signer(
-                            ctx.accounts.token_program.to_account_info(),
-                            token::Transfer {
-                                from: custodian_token_acct.to_account_info(),
-                                to: buyer_token_acct.to_account_info(),
-                                authority: transfer_authority.to_account_info(),
-                            },
-                            &[&[SEED_PREFIX_CUSTODIAN.as_bytes(), &[ctx.bumps["custodian"]]]],
-                        ),
-                        refund,
-                    )?;
+                    unwrap_token_acct: buyer_token_acct,
+                    native_mint: &ctx.accounts.native_mint.to_account_info(),
+                    owner: &owner.to_account_info(),
+                    system_program: &ctx.accounts.system_program.to_account_info(),
+                    token_program: &ctx.accounts.token_program.to_account_info(),
                 }
-                (None, None) => {
-                    // This scenario is expected for an invalid token because
-                    // neither will have an associated token account
-                }
-                _ => return Err(ContributorError::InvalidAccount.into()),
-            };
+                .unwrap(refund, ctx.bumps["custodian"])?;
+            } else {
+                // Verify remaining accounts are associated token accounts.
+                // Either both are valid or both are invalid. If only one
+                // is valid, then there is something wrong.
+                // In the case that both are invalid, this is when the accepted
+                // token itself is invalid.
+                match (
+                    asset.deserialize_associated_token_account(
+                        custodian_token_acct,
+                        &ctx.accounts.custodian.key(),
+                    )?,
+                    asset.deserialize_associated_token_account(buyer_token_acct, &owner.key())?,
+                ) {
+                    (Some(_), Some(_)) => {
+                        token::transfer(
+                            CpiContext::new_with_signer(
+                                ctx.accounts.token_program.to_account_info(),
+                                token::Transfer {
+                                    from: custodian_token_acct.to_account_info(),
+                                    to: buyer_token_acct.to_account_info(),
+                                    authority: transfer_authority.to_account_info(),
+                                },
+                                &[&[SEED_PREFIX_CUSTODIAN.as_bytes(), &[ctx.bumps["custodian"]]]],
+                            ),
+                            refund,
+                        )?;
+                    }
+                    (None, None) => {
+                        // This scenario is expected for an invalid token because
+                        // neither will have an associated token account
+                    }
+                    _ => return Err(ContributorError::InvalidAccount.into()),
+                };
+            }
+
+            emit!(RefundClaimed {
+                sale_id: sale.id,
+                buyer: owner.key(),
+                token_index: asset.token_index,
+                amount: refund,
+            });
         }
 
         // Finish instruction.
@@ -756,6 +930,12 @@ pub mod anchor_contributor {
             allocation,
         )?;
 
+        emit!(AllocationClaimed {
+            sale_id: sale.id,
+            buyer: ctx.accounts.owner.key(),
+            amount: allocation,
+        });
+
         // Finish instruction.
         Ok(())
     }
@@ -770,6 +950,8 @@ pub mod anchor_contributor {
     ///
     /// There are n transfers for the excesses, depending on however many tokens a user has
     /// contributed to the sale.
+    ///
+    /// Like `claim_refunds`, excess wrapped SOL is paid out in lamports.
     pub fn claim_excesses<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, ClaimExcesses<'info>>,
     ) -> Result<()> {
@@ -797,11 +979,6 @@ pub mod anchor_contributor {
         let owner = &ctx.accounts.owner;
         let transfer_authority = &ctx.accounts.custodian;
 
-        // This is used in case we need to use a native solana transfer.
-        //
-        // let mut all_accts = ctx.accounts.to_account_infos();
-        // all_accts.extend_from_slice(&ctx.remaining_accounts);
-
         // We will mutate the buyer's accounting and state for each contributed mint.
         let buyer = &mut ctx.accounts.buyer;
         for (idx, (asset, custodian_token_acct, buyer_token_acct)) in
@@ -814,38 +991,70 @@ pub mod anchor_contributor {
                 continue;
             }
 
-            // Verify remaining accounts are associated token accounts.
-            // Either both are valid or both are invalid. If only one
-            // is valid, then there is something wrong.
-            // In the case that both are invalid, this is when the accepted
-            // token itself is invalid.
-            match (
-                asset.deserialize_associated_token_account(
+            // Wrapped SOL is paid out in lamports through a temporary token account
+            // owned by the custodian, which is passed in place of the buyer's
+            // associated token account.
+            if asset.mint == spl_token::native_mint::id() {
+                require!(
+                    asset
+                        .deserialize_associated_token_account(
+                            custodian_token_acct,
+                            &ctx.accounts.custodian.key(),
+                        )?
+                        .is_some(),
+                    ContributorError::InvalidAccount
+                );
+                UnwrapNative {
+                    custodian: &transfer_authority.to_account_info(),
                     custodian_token_acct,
-                    &ctx.accounts.custodian.key(),
-                )?,
-                asset.deserialize_associated_token_account(buyer_token_acct, &owner.key())?,
-            ) {
-                (Some(_), Some(_)) => {
-                    token::transfer(
-                        CpiContext::new_with_signer(
-                            ctx.accounts.token_program.to_account_info(),
-                            token::Transfer {
-                                from: custodian_token_acct.to_account_info(),
-                                to: buyer_token_acct.to_account_info(),
-                                authority: transfer_authority.to_account_info(),
-                            },
-                            &[&[SEED_PREFIX_CUSTODIAN.as_bytes(), &[ctx.bumps["custodian"]]]],
-                        ),
-                        excess,
-                    )?;
-                }
-                (None, None) => {
-                    // This scenario is expected for an invalid token because
-                    // neither will have an associated token account
+                    unwrap_token_acct: buyer_token_acct,
+                    native_mint: &ctx.accounts.native_mint.to_account_info(),
+                    owner: &owner.to_account_info(),
+                    system_program: &ctx.accounts.system_program.to_account_info(),
+                    token_program: &ctx.accounts.token_program.to_account_info(),
                 }
-                _ => return Err(ContributorError::InvalidAccount.into()),
-            };
+                .unwrap(excess, ctx.bumps["custodian"])?;
+            } else {
+                // Verify remaining accounts are associated token accounts.
+                // Either both are valid or both are invalid. If only one
+                // is valid, then there is something wrong.
+                // In the case that both are invalid, this is when the accepted
+                // token itself is invalid.
+                match (
+                    asset.deserialize_associated_token_account(
+                        custodian_token_acct,
+                        &ctx.accounts.custodian.key(),
+                    )?,
+                    asset.deserialize_associated_token_account(buyer_token_acct, &owner.key())?,
+                ) {
+                    (Some(_), Some(_)) => {
+                        token::transfer(
+                            CpiContext::new_with_signer(
+                                ctx.accounts.token_program.to_account_info(),
+                                token::Transfer {
+                                    from: custodian_token_acct.to_account_info(),
+                                    to: buyer_token_acct.to_account_info(),
+                                    authority: transfer_authority.to_account_info(),
+                                },
+                                &[&[SEED_PREFIX_CUSTODIAN.as_bytes(), &[ctx.bumps["custodian"]]]],
+                            ),
+                            excess,
+                        )?;
+                    }
+                    (None, None) => {
+                        // This scenario is expected for an invalid token because
+                        // neither will have an associated token account
+                    }
+                    _ => return Err(ContributorError::InvalidAccount.into()),
+                };
+            }
+
+            emit!(ExcessClaimed {
+                sale_id: sale.id,
+                buyer: owner.key(),
+                token_index: asset.token_index,
+                amount: excess,
+            });
         }
 
         // Finish instruction.
@@ -874,8 +1083,128 @@ pub mod anchor_contributor {
                 sale.id,
             )?;
 
-        // Finish the instruction by updating the KYC authority if the sale is still active.
+        // Mark the VAA as consumed.
+        ctx.accounts.claim.claim()?;
+
+        // Update the KYC authority if the sale is still active.
         let clock = Clock::get()?;
-        sale.parse_kyc_authority_updated(clock.unix_timestamp, &msg.payload)
+        sale.parse_kyc_authority_updated(clock.unix_timestamp, &msg.payload)?;
+
+        emit!(KycAuthorityUpdated {
+            sale_id: sale.id,
+            kyc_authority: sale.kyc_authority,
+        });
+
+        // Finish instruction.
+        Ok(())
+    }
+
+    /// Instruction to act on a signed VAA sent by the governance emitter. This works
+    /// the same way as governance for the Terra contributor, and there are two actions:
+    /// replacing the conductor and upgrading the program.
+    ///
+    /// A `Claim` account is created for the VAA, seeded by its emitter and sequence,
+    /// so the same VAA cannot be acted on twice.
+    ///
+    /// The upgrade is done through the BPF upgradeable loader. The program's upgrade
+    /// authority needs to be handed to the PDA seeded by `SEED_PREFIX_UPGRADE_AUTHORITY`
+    /// beforehand, so the program can sign for the upgrade itself.
+    pub fn submit_governance<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SubmitGovernance<'info>>,
+    ) -> Result<()> {
+        // We need to verify that the signed VAA was emitted from the governance
+        // emitter and that it is meant for this contributor.
+        let msg = ctx
+            .accounts
+            .custodian
+            .parse_and_verify_governance_vaa(&ctx.accounts.core_bridge_vaa)?;
+
+        // Mark the VAA as consumed. If this VAA has been submitted before, we
+        // cannot continue.
+        ctx.accounts.claim.claim()?;
+
+        match msg.payload[INDEX_GOVERNANCE_ACTION] {
+            GOVERNANCE_ACTION_UPDATE_CONDUCTOR => {
+                let custodian = &mut ctx.accounts.custodian;
+                custodian.update_conductor(&msg.payload)?;
+
+                emit!(ConductorUpdated {
+                    conductor_chain: custodian.conductor_chain,
+                    conductor_address: custodian.conductor_address,
+                });
+            }
+            GOVERNANCE_ACTION_UPGRADE_CONTRACT => {
+                let buffer = Custodian::get_upgrade_buffer_from_payload(&msg.payload)?;
+
+                // We pass as an extra argument remaining accounts. These are the accounts
+                // the BPF upgradeable loader needs for the upgrade, followed by the loader.
+                let upgrade_accts = &ctx.remaining_accounts;
+                require!(
+                    upgrade_accts.len() == 7,
+                    ContributorError::InvalidRemainingAccounts
+                );
+                let program_data_acct = &upgrade_accts[0];
+                let program_acct = &upgrade_accts[1];
+                let buffer_acct = &upgrade_accts[2];
+                let upgrade_authority = &upgrade_accts[3];
+                let rent_acct = &upgrade_accts[4];
+                let clock_acct = &upgrade_accts[5];
+                let loader_acct = &upgrade_accts[6];
+
+                require!(
+                    program_acct.key() == *ctx.program_id,
+                    ContributorError::InvalidAccount
+                );
+                require!(
+                    buffer_acct.key() == buffer,
+                    ContributorError::InvalidUpgradeBuffer
+                );
+                require!(
+                    rent_acct.key() == rent::id() && clock_acct.key() == clock::id(),
+                    ContributorError::InvalidSystemProgram
+                );
+                require!(
+                    loader_acct.key() == bpf_loader_upgradeable::id(),
+                    ContributorError::InvalidAccount
+                );
+
+                let (authority_key, authority_bump) = Pubkey::find_program_address(
+                    &[SEED_PREFIX_UPGRADE_AUTHORITY.as_bytes()],
+                    ctx.program_id,
+                );
+                require!(
+                    upgrade_authority.key() == authority_key,
+                    ContributorError::InvalidAccount
+                );
+
+                // Any lamports left in the buffer go to the payer.
+                let payer = &ctx.accounts.payer;
+                invoke_signed(
+                    &bpf_loader_upgradeable::upgrade(
+                        &program_acct.key(),
+                        &buffer,
+                        &authority_key,
+                        &payer.key(),
+                    ),
+                    &[
+                        program_data_acct.to_account_info(),
+                        program_acct.to_account_info(),
+                        buffer_acct.to_account_info(),
+                        payer.to_account_info(),
+                        rent_acct.to_account_info(),
+                        clock_acct.to_account_info(),
+                        upgrade_authority.to_account_info(),
+                        loader_acct.to_account_info(),
+                    ],
+                    &[&[SEED_PREFIX_UPGRADE_AUTHORITY.as_bytes(), &[authority_bump]]],
+                )?;
+
+                emit!(ContractUpgraded { buffer });
+            }
+            _ => return Err(ContributorError::InvalidVaaAction.into()),
+        }
+
+        // Finish instruction.
+        Ok(())
     }
 }
@@ -0,0 +1,138 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        program::{invoke, invoke_signed},
+        system_instruction,
+    },
+};
+use anchor_spl::token::{self, TokenAccount};
+
+use crate::{constants::*, error::ContributorError};
+
+/// Accounts needed to pay out wrapped SOL held by the custodian as lamports.
+///
+/// The wrapped SOL is moved into a temporary token account that the custodian
+/// owns, which is then closed to `owner`. This way the buyer does not need a
+/// wrapped SOL account of their own, and whatever is already in one stays
+/// there.
+pub struct UnwrapNative<'a, 'info> {
+    pub custodian: &'a AccountInfo<'info>,
+    pub custodian_token_acct: &'a AccountInfo<'info>,
+    pub unwrap_token_acct: &'a AccountInfo<'info>,
+    pub native_mint: &'a AccountInfo<'info>,
+    pub owner: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> UnwrapNative<'a, 'info> {
+    /// Sends `amount` of the custodian's wrapped SOL to `owner` as lamports.
+    /// `owner` pays the rent for the temporary account and gets it back when
+    /// it is closed.
+    pub fn unwrap(&self, amount: u64, custodian_bump: u8) -> Result<()> {
+        let owner_key = self.owner.key();
+        let (unwrap_key, unwrap_bump) = Pubkey::find_program_address(
+            &[SEED_PREFIX_UNWRAP.as_bytes(), owner_key.as_ref()],
+            &crate::ID,
+        );
+        require!(
+            self.unwrap_token_acct.key() == unwrap_key,
+            ContributorError::InvalidAccount
+        );
+        let unwrap_seeds: &[&[u8]] = &[
+            SEED_PREFIX_UNWRAP.as_bytes(),
+            owner_key.as_ref(),
+            &[unwrap_bump],
+        ];
+        let custodian_seeds: &[&[u8]] = &[SEED_PREFIX_CUSTODIAN.as_bytes(), &[custodian_bump]];
+
+        self.create_unwrap_token_acct(unwrap_seeds)?;
+        invoke(
+            &spl_token::instruction::initialize_account3(
+                &token::ID,
+                &unwrap_key,
+                &spl_token::native_mint::id(),
+                &self.custodian.key(),
+            )?,
+            &[self.unwrap_token_acct.clone(), self.native_mint.clone()],
+        )?;
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                token::Transfer {
+                    from: self.custodian_token_acct.clone(),
+                    to: self.unwrap_token_acct.clone(),
+                    authority: self.custodian.clone(),
+                },
+                &[custodian_seeds],
+            ),
+            amount,
+        )?;
+
+        // Closing the account pays out its rent and the unwrapped SOL.
+        token::close_account(CpiContext::new_with_signer(
+            self.token_program.clone(),
+            token::CloseAccount {
+                account: self.unwrap_token_acct.clone(),
+                destination: self.owner.clone(),
+                authority: self.custodian.clone(),
+            },
+            &[custodian_seeds],
+        ))
+    }
+
+    // Anyone can send lamports to the temporary account's address, so it is
+    // created the same way Anchor's `init` does it instead of failing in
+    // `create_account`.
+    fn create_unwrap_token_acct(&self, unwrap_seeds: &[&[u8]]) -> Result<()> {
+        let space = TokenAccount::LEN;
+        let required = Rent::get()?.minimum_balance(space);
+        let current = self.unwrap_token_acct.lamports();
+
+        if current == 0 {
+            return invoke_signed(
+                &system_instruction::create_account(
+                    &self.owner.key(),
+                    &self.unwrap_token_acct.key(),
+                    required,
+                    space as u64,
+                    &token::ID,
+                ),
+                &[
+                    self.owner.clone(),
+                    self.unwrap_token_acct.clone(),
+                    self.system_program.clone(),
+                ],
+                &[unwrap_seeds],
+            )
+            .map_err(Into::into);
+        }
+
+        if required > current {
+            invoke(
+                &system_instruction::transfer(
+                    &self.owner.key(),
+                    &self.unwrap_token_acct.key(),
+                    required - current,
+                ),
+                &[
+                    self.owner.clone(),
+                    self.unwrap_token_acct.clone(),
+                    self.system_program.clone(),
+                ],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(&self.unwrap_token_acct.key(), space as u64),
+            &[self.unwrap_token_acct.clone(), self.system_program.clone()],
+            &[unwrap_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(&self.unwrap_token_acct.key(), &token::ID),
+            &[self.unwrap_token_acct.clone(), self.system_program.clone()],
+            &[unwrap_seeds],
+        )
+        .map_err(Into::into)
+    }
+}
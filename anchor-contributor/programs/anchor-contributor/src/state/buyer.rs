@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use num_derive::*;
 
-use crate::{constants::ACCEPTED_TOKENS_MAX, error::ContributorError, state::sale::AssetTotal};
+use crate::{error::ContributorError, state::sale::AssetTotal};
 
 #[derive(
     AnchorSerialize, AnchorDeserialize, FromPrimitive, ToPrimitive, Copy, Clone, PartialEq, Eq,
@@ -50,7 +50,7 @@ pub struct Buyer {
     /// `Buyer` needs to keep track of a user's contribution amounts
     /// and excess after a sealed sale
     ///
-    /// Borsh size: 4 + BuyerTotal::LENGTH * ACCEPTED_TOKENS_MAX
+    /// Borsh size: 4 + BuyerContribution::LENGTH * number of accepted tokens
     pub contributions: Vec<BuyerContribution>,
     /// At the time of the `claim_allocation` instruction, we keep
     /// a record of how much allocation `amount` the `Buyer` is owed
@@ -74,8 +74,11 @@ impl BuyerAllocation {
 }
 
 impl Buyer {
-    pub const MAXIMUM_SIZE: usize =
-        (4 + BuyerContribution::LENGTH * ACCEPTED_TOKENS_MAX) + BuyerAllocation::LENGTH + 1;
+    /// A `Buyer` is created once the sale has every accepted token, so it is
+    /// sized for exactly that many contributions
+    pub fn get_size(num_totals: usize) -> usize {
+        (4 + BuyerContribution::LENGTH * num_totals) + BuyerAllocation::LENGTH + 1
+    }
 
     /// If a `Buyer` account hasn't been created yet, set up initial state
     ///
@@ -114,9 +117,12 @@ impl Buyer {
             ContributorError::ContributeDeactivated
         );
 
-        let total = &mut self.contributions[idx];
-        total.amount += amount;
-        total.status = ContributionStatus::Active;
+        let contribution = &mut self.contributions[idx];
+        contribution.amount = contribution
+            .amount
+            .checked_add(amount)
+            .ok_or(ContributorError::AmountTooLarge)?;
+        contribution.status = ContributionStatus::Active;
         Ok(())
     }
 
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ContributorError, wormhole::get_message_data};
+
+#[account]
+#[derive(Default)]
+/// `Claim` marks a VAA as consumed. It is seeded by the VAA's emitter and
+/// sequence, so each VAA can only be acted on once.
+pub struct Claim {
+    /// Whether the VAA has already been processed
+    ///
+    /// Borsh size: 1
+    pub claimed: bool,
+}
+
+impl Claim {
+    pub const MAXIMUM_SIZE: usize = 1;
+
+    pub fn claim(&mut self) -> Result<()> {
+        require!(!self.claimed, ContributorError::VaaAlreadyClaimed);
+        self.claimed = true;
+        Ok(())
+    }
+
+    pub fn get_emitter_address_from_vaa<'info>(vaa_acct: &AccountInfo<'info>) -> Result<[u8; 32]> {
+        Ok(get_message_data(&vaa_acct)?.emitter_address)
+    }
+
+    pub fn get_emitter_chain_from_vaa<'info>(vaa_acct: &AccountInfo<'info>) -> Result<[u8; 2]> {
+        Ok(get_message_data(&vaa_acct)?.emitter_chain.to_be_bytes())
+    }
+
+    pub fn get_sequence_from_vaa<'info>(vaa_acct: &AccountInfo<'info>) -> Result<[u8; 8]> {
+        Ok(get_message_data(&vaa_acct)?.sequence.to_be_bytes())
+    }
+}
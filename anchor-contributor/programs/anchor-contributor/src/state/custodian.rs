@@ -1,52 +1,107 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    constants::INDEX_SALE_ID,
-    env::*,
+    constants::{
+        CHAIN_ID, GOVERNANCE_ACTION_UPDATE_CONDUCTOR, GOVERNANCE_ACTION_UPGRADE_CONTRACT,
+        GOVERNANCE_MODULE, INDEX_GOVERNANCE_ACTION, INDEX_GOVERNANCE_CHAIN,
+        INDEX_GOVERNANCE_PAYLOAD, INDEX_SALE_ID,
+    },
     error::ContributorError,
-    wormhole::{get_message_data, MessageData},
+    wormhole::{get_message_data, get_verified_message_data, MessageData},
 };
 
-use std::str::FromStr;
-
 #[account]
 #[derive(Default)]
+/// `Custodian` holds every associated token account for all sales and knows
+/// which conductor and Wormhole programs the contributor trusts. These are
+/// set once by `create_custodian`, so the same build can be deployed to any
+/// cluster. Only the conductor can be changed afterwards, through a VAA
+/// emitted by the governance emitter (see `submit_governance`).
 pub struct Custodian {
+    /// Upgrade authority of the program when the custodian was created
+    ///
+    /// Borsh size: 32
+    pub owner: Pubkey,
+    /// Wormhole chain ID of the conductor
+    ///
+    /// Borsh size: 2
+    pub conductor_chain: u16,
+    /// Conductor's emitter address, left-zero-padded if shorter than 32 bytes
+    ///
+    /// Borsh size: 32
+    pub conductor_address: [u8; 32],
+    /// Minimum consistency level of the conductor's VAAs. Its meaning depends on
+    /// the conductor's chain. A governance conductor update does not carry one,
+    /// so it is kept
+    ///
+    /// Borsh size: 1
+    pub conductor_consistency_level: u8,
+    /// Wormhole chain ID of the governance emitter
+    ///
+    /// Borsh size: 2
+    pub governance_chain: u16,
+    /// Governance emitter address
+    ///
+    /// Borsh size: 32
+    pub governance_address: [u8; 32],
+    /// Wormhole core bridge program
+    ///
+    /// Borsh size: 32
+    pub wormhole: Pubkey,
+    /// Wormhole token bridge program
+    ///
+    /// Borsh size: 32
+    pub token_bridge: Pubkey,
+}
+
+/// Emitters and programs the custodian is created with. See `create_custodian`.
+pub struct CustodianConfig {
+    pub conductor_chain: u16,
+    pub conductor_address: [u8; 32],
+    pub conductor_consistency_level: u8,
+    pub governance_chain: u16,
+    pub governance_address: [u8; 32],
+    pub wormhole: Pubkey,
+    pub token_bridge: Pubkey,
 }
 
 impl Custodian {
-    pub const MAXIMUM_SIZE: usize = 0;
-
-    pub fn conductor_chain() -> Result<u16> {
-        let chain_id = CONDUCTOR_CHAIN
-            .to_string()
-            .parse()
-            .map_err(|_| ContributorError::InvalidConductorChain)?;
-        Ok(chain_id)
-    }
+    pub const MAXIMUM_SIZE: usize = 32 + 2 + 32 + 1 + 2 + 32 + 32 + 32;
 
-    pub fn conductor_address() -> Result<[u8; 32]> {
-        let mut addr = [0u8; 32];
-        addr.copy_from_slice(
-            &hex::decode(CONDUCTOR_ADDRESS)
-                .map_err(|_| ContributorError::InvalidConductorAddress)?,
+    pub fn new(&mut self, owner: &Pubkey, config: CustodianConfig) -> Result<()> {
+        require!(
+            config.conductor_chain != 0 && config.conductor_chain != CHAIN_ID,
+            ContributorError::InvalidConductorChain
+        );
+        require!(
+            config.conductor_address != [0u8; 32],
+            ContributorError::InvalidConductorAddress
+        );
+        require!(
+            config.governance_chain != 0,
+            ContributorError::InvalidGovernanceChain
+        );
+        require!(
+            config.governance_address != [0u8; 32],
+            ContributorError::InvalidGovernanceAddress
+        );
+        require!(
+            config.wormhole != Pubkey::default(),
+            ContributorError::InvalidWormholeAddress
+        );
+        require!(
+            config.token_bridge != Pubkey::default(),
+            ContributorError::InvalidTokenBridgeAddress
         );
-        Ok(addr)
-    }
-
-    pub fn wormhole() -> Result<Pubkey> {
-        let pubkey = Pubkey::from_str(CORE_BRIDGE_ADDRESS)
-            .map_err(|_| ContributorError::InvalidWormholeAddress)?;
-        Ok(pubkey)
-    }
-
-    pub fn token_bridge() -> Result<Pubkey> {
-        let pubkey = Pubkey::from_str(TOKEN_BRIDGE_ADDRESS)
-            .map_err(|_| ContributorError::InvalidWormholeAddress)?;
-        Ok(pubkey)
-    }
 
-    pub fn new(&mut self) -> Result<()> {
+        self.owner = *owner;
+        self.conductor_chain = config.conductor_chain;
+        self.conductor_address = config.conductor_address;
+        self.conductor_consistency_level = config.conductor_consistency_level;
+        self.governance_chain = config.governance_chain;
+        self.governance_address = config.governance_address;
+        self.wormhole = config.wormhole;
+        self.token_bridge = config.token_bridge;
         Ok(())
     }
 
@@ -55,13 +110,14 @@ impl Custodian {
         vaa_acct: &AccountInfo<'info>,
         payload_type: u8,
     ) -> Result<MessageData> {
-        let msg = get_message_data(&vaa_acct)?;
+        let msg =
+            get_verified_message_data(&vaa_acct, &self.wormhole, self.conductor_consistency_level)?;
         require!(
-            msg.emitter_chain == Custodian::conductor_chain()?,
+            msg.emitter_chain == self.conductor_chain,
             ContributorError::InvalidConductorChain
         );
         require!(
-            msg.emitter_address == Custodian::conductor_address()?,
+            msg.emitter_address == self.conductor_address,
             ContributorError::InvalidConductorAddress
         );
         require!(
@@ -71,6 +127,99 @@ impl Custodian {
         Ok(msg)
     }
 
+    /// Verifies that the VAA was emitted by the governance emitter and that
+    /// its payload is addressed to this contributor. The packet layout is the
+    /// same as the one the Terra contributor accepts: a 32-byte module name,
+    /// an action, the target chain (0 for every chain, which only conductor
+    /// updates may use) and the action's payload.
+    pub fn parse_and_verify_governance_vaa<'info>(
+        &self,
+        vaa_acct: &AccountInfo<'info>,
+    ) -> Result<MessageData> {
+        // governance VAAs are produced by the guardians themselves, so there is
+        // no emitter chain finality to wait for
+        let msg = get_verified_message_data(&vaa_acct, &self.wormhole, 0)?;
+        require!(
+            msg.emitter_chain == self.governance_chain,
+            ContributorError::InvalidGovernanceChain
+        );
+        require!(
+            msg.emitter_address == self.governance_address,
+            ContributorError::InvalidGovernanceAddress
+        );
+
+        let payload = &msg.payload;
+        require!(
+            payload.len() >= INDEX_GOVERNANCE_PAYLOAD,
+            ContributorError::InvalidVaaPayload
+        );
+
+        // module name is left-zero-padded to 32 bytes
+        let module_start = INDEX_GOVERNANCE_ACTION - GOVERNANCE_MODULE.len();
+        require!(
+            payload[..module_start].iter().all(|&b| b == 0)
+                && &payload[module_start..INDEX_GOVERNANCE_ACTION] == GOVERNANCE_MODULE,
+            ContributorError::InvalidGovernanceModule
+        );
+
+        let action = payload[INDEX_GOVERNANCE_ACTION];
+        require!(
+            action == GOVERNANCE_ACTION_UPGRADE_CONTRACT
+                || action == GOVERNANCE_ACTION_UPDATE_CONDUCTOR,
+            ContributorError::InvalidVaaAction
+        );
+
+        let target_chain = u16::from_be_bytes(
+            payload[INDEX_GOVERNANCE_CHAIN..INDEX_GOVERNANCE_PAYLOAD]
+                .try_into()
+                .unwrap(),
+        );
+        // an upgrade has to name this chain explicitly
+        require!(
+            target_chain == CHAIN_ID
+                || (target_chain == 0 && action == GOVERNANCE_ACTION_UPDATE_CONDUCTOR),
+            ContributorError::InvalidGovernanceTargetChain
+        );
+        Ok(msg)
+    }
+
+    /// Replaces the conductor with the chain and address found in an "update
+    /// conductor" governance payload.
+    pub fn update_conductor(&mut self, payload: &[u8]) -> Result<()> {
+        require!(
+            payload.len() == INDEX_GOVERNANCE_PAYLOAD + 34,
+            ContributorError::InvalidVaaPayload
+        );
+        let data = &payload[INDEX_GOVERNANCE_PAYLOAD..];
+
+        let conductor_chain = u16::from_be_bytes(data[0..2].try_into().unwrap());
+        require!(
+            conductor_chain != 0 && conductor_chain != CHAIN_ID,
+            ContributorError::InvalidConductorChain
+        );
+
+        let mut conductor_address = [0u8; 32];
+        conductor_address.copy_from_slice(&data[2..34]);
+        require!(
+            conductor_address != [0u8; 32],
+            ContributorError::InvalidConductorAddress
+        );
+
+        self.conductor_chain = conductor_chain;
+        self.conductor_address = conductor_address;
+        Ok(())
+    }
+
+    /// Reads the buffer account holding the new program from an "upgrade
+    /// contract" governance payload.
+    pub fn get_upgrade_buffer_from_payload(payload: &[u8]) -> Result<Pubkey> {
+        require!(
+            payload.len() == INDEX_GOVERNANCE_PAYLOAD + 32,
+            ContributorError::InvalidVaaPayload
+        );
+        Ok(Pubkey::new(&payload[INDEX_GOVERNANCE_PAYLOAD..]))
+    }
+
     pub fn get_sale_id_from_payload(payload: &[u8]) -> [u8; 32] {
         let mut sale_id = [0u8; 32];
         sale_id.copy_from_slice(&payload[INDEX_SALE_ID..INDEX_SALE_ID + 32]);
@@ -1,7 +1,9 @@
 pub use buyer::*;
+pub use claim::*;
 pub use custodian::*;
 pub use sale::*;
 
 pub mod buyer;
+pub mod claim;
 pub mod custodian;
 pub mod sale;
@@ -1,15 +1,20 @@
-use anchor_lang::{prelude::*, solana_program::keccak};
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        entrypoint::MAX_PERMITTED_DATA_INCREASE, keccak, program::invoke,
+        system_instruction::transfer,
+    },
+};
 use anchor_spl::{
     associated_token::get_associated_token_address,
-    token::{Mint, TokenAccount},
+    token::{self, Mint, TokenAccount},
 };
 use num::{bigint::BigUint, traits::ToPrimitive};
 use num_derive::*;
 use std::{mem::size_of_val, u64};
 
 use crate::{
-    constants::*, cryptography::ethereum_ecrecover, error::ContributorError,
-    state::custodian::Custodian,
+    constants::*, cryptography::ethereum_ecrecover, error::ContributorError, wormhole::MessageData,
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, PartialEq, Eq)]
@@ -59,12 +64,14 @@ pub struct Sale {
     pub recipient: [u8; 32],     // 32
     pub status: SaleStatus,      // 1
     pub kyc_authority: [u8; 20], // 20 (this is an evm pubkey)
-    pub initialized: bool,       // 1
-
-    pub totals: Vec<AssetTotal>, // 4 + AssetTotal::LEN * ACCEPTED_TOKENS_MAX
-    pub native_token_decimals: u8, // 1
-    pub sale_token_mint: Pubkey, // 32   Solana Native or wrapped.
-    pub sale_token_ata: Pubkey,  // 32
+    pub initialized: bool,       // 1    True once every accepted token is added.
+    pub init_vaa_hash: [u8; 32], // 32   Accepted tokens are only added from this VAA.
+
+    pub num_accepted: u8,            // 1
+    pub totals: Vec<AssetTotal>,     // 4 + AssetTotal::LEN * num_accepted
+    pub native_token_decimals: u8,   // 1
+    pub sale_token_mint: Pubkey,     // 32   Solana Native or wrapped.
+    pub sale_token_ata: Pubkey,      // 32
     pub contributions_blocked: bool, // 1 Bad sale token mint address.
 }
 
@@ -148,24 +155,57 @@ impl AssetTotal {
 }
 
 impl Sale {
-    pub const MAXIMUM_SIZE: usize = 32
-        + 32
-        + 2
-        + 1
-        + SaleTimes::LEN
-        + 32
-        + 1
-        + 20
-        + 1
-        + (4 + AssetTotal::LEN * ACCEPTED_TOKENS_MAX)
-        + 1
-        + 32
-        + 32
-        + 1;
-
-    pub fn parse_sale_init(&mut self, payload: &[u8]) -> Result<()> {
+    /// Size without any `AssetTotal` elements
+    pub const BASE_SIZE: usize =
+        32 + 32 + 2 + 1 + SaleTimes::LEN + 32 + 1 + 20 + 1 + 32 + 1 + 4 + 1 + 32 + 32 + 1;
+
+    pub fn get_size(num_totals: usize) -> usize {
+        Sale::BASE_SIZE + AssetTotal::LEN * num_totals
+    }
+
+    /// Grows the Sale account so it can hold `num_totals` accepted tokens, with the
+    /// payer covering the additional rent. The account starts out without any (see
+    /// `InitSale`), and `init_sale` and `add_accepted_tokens` grow it by however many
+    /// accepted token mints they are passed.
+    pub fn grow_account<'info>(
+        sale_acct_info: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        num_totals: usize,
+    ) -> Result<()> {
+        let new_len = 8 + Sale::get_size(num_totals);
+        let current_len = sale_acct_info.data_len();
+        if new_len <= current_len {
+            return Ok(());
+        }
+        require!(
+            new_len - current_len <= MAX_PERMITTED_DATA_INCREASE,
+            ContributorError::TooManyAcceptedTokens
+        );
+
+        let rent_owed = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(sale_acct_info.lamports());
+        if rent_owed > 0 {
+            invoke(
+                &transfer(&payer.key(), &sale_acct_info.key(), rent_owed),
+                &[
+                    payer.clone(),
+                    sale_acct_info.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+        sale_acct_info.realloc(new_len, false)?;
+        Ok(())
+    }
+
+    /// Parses everything in the sale init payload except the accepted tokens,
+    /// which are added afterwards with `add_accepted_tokens`. The VAA's hash is
+    /// saved so those can only come from the same VAA.
+    pub fn parse_sale_init(&mut self, msg: &MessageData) -> Result<()> {
         require!(!self.initialized, ContributorError::SaleAlreadyInitialized);
-        self.initialized = true;
+        let payload = &msg.payload;
 
         // check that the payload has at least the number of bytes
         // required to define the number of accepted tokens
@@ -175,6 +215,10 @@ impl Sale {
         );
 
         let num_accepted = payload[INDEX_SALE_INIT_ACCEPTED_TOKENS_START] as usize;
+        require!(
+            num_accepted <= ACCEPTED_TOKENS_MAX,
+            ContributorError::TooManyAcceptedTokens
+        );
 
         require!(
             payload.len()
@@ -185,18 +229,9 @@ impl Sale {
             ContributorError::InvalidVaaPayload
         );
 
-        require!(
-            num_accepted <= ACCEPTED_TOKENS_MAX,
-            ContributorError::TooManyAcceptedTokens
-        );
-
+        self.num_accepted = num_accepted as u8;
         self.totals = Vec::with_capacity(num_accepted);
-        for i in 0..num_accepted {
-            let start = INDEX_SALE_INIT_ACCEPTED_TOKENS_START + 1 + ACCEPTED_TOKEN_NUM_BYTES * i;
-            self.totals.push(AssetTotal::make_from_slice(
-                &payload[start..(start + ACCEPTED_TOKEN_NUM_BYTES)],
-            )?);
-        }
+        self.init_vaa_hash = msg.hash();
 
         self.id = Sale::get_id(payload);
 
@@ -232,6 +267,74 @@ impl Sale {
         Ok(())
     }
 
+    /// Adds the next accepted tokens from the sale init VAA, one for each mint in
+    /// `accepted_mints` (in the same order as the payload). Accepted tokens whose
+    /// mint is not a real SPL token are invalidated. The sale is initialized once
+    /// every accepted token is added.
+    pub fn add_accepted_tokens(
+        &mut self,
+        msg: &MessageData,
+        accepted_mints: &[AccountInfo],
+    ) -> Result<()> {
+        require!(!self.initialized, ContributorError::SaleAlreadyInitialized);
+        require!(
+            msg.hash() == self.init_vaa_hash,
+            ContributorError::InvalidSaleInitVaa
+        );
+        let payload = &msg.payload;
+
+        let num_accepted = self.num_accepted as usize;
+        let num_added = self.totals.len();
+        require!(
+            num_added + accepted_mints.len() <= num_accepted,
+            ContributorError::InvalidRemainingAccounts
+        );
+
+        for (i, accepted_mint_acct_info) in (num_added..).zip(accepted_mints) {
+            let start = INDEX_SALE_INIT_ACCEPTED_TOKENS_START + 1 + ACCEPTED_TOKEN_NUM_BYTES * i;
+            let mut asset = AssetTotal::make_from_slice(
+                payload
+                    .get(start..(start + ACCEPTED_TOKEN_NUM_BYTES))
+                    .ok_or(ContributorError::InvalidVaaPayload)?,
+            )?;
+
+            // If the remaining account does not match the key of the accepted asset's mint,
+            // throw because wrong account is passed into instruction.
+            require!(
+                accepted_mint_acct_info.key() == asset.mint,
+                ContributorError::InvalidRemainingAccounts,
+            );
+
+            // Check whether we should invalidate the accepted asset.
+            match *accepted_mint_acct_info.owner == token::ID {
+                false => {
+                    // If the remaining account is not owned by token program, it is invalid.
+                    asset.invalidate();
+                }
+                _ => {
+                    match accepted_mint_acct_info.try_borrow_data() {
+                        Err(_) => {
+                            // If the remaining account is not a real account, it is invalid.
+                            asset.invalidate();
+                        }
+                        Ok(data) => {
+                            // If the remaining account does not deserialize to Mint account, it is invalid.
+                            let mut bf: &[u8] = &data;
+                            if Mint::try_deserialize(&mut bf).is_err() {
+                                asset.invalidate();
+                            }
+                        }
+                    }
+                }
+            };
+
+            self.totals.push(asset);
+        }
+
+        self.initialized = self.totals.len() == num_accepted;
+        Ok(())
+    }
+
     pub fn set_sale_token_mint_info(
         &mut self,
         mint: &Pubkey,
@@ -276,7 +379,12 @@ impl Sale {
             block_time >= self.times.start,
             ContributorError::ContributionTooEarly
         );
-        self.totals[asset_total_idx].contributions += contributed;
+
+        let total = &mut self.totals[asset_total_idx];
+        total.contributions = total
+            .contributions
+            .checked_add(contributed)
+            .ok_or(ContributorError::AmountTooLarge)?;
 
         Ok(())
     }
@@ -402,6 +510,7 @@ impl Sale {
 
     pub fn verify_kyc_authority(
         &self,
+        conductor_address: &[u8; 32],
         token_index: u8,
         amount: u64,
         buyer: &Pubkey,
@@ -415,8 +524,8 @@ impl Sale {
         // first encode arguments
         let mut encoded: Vec<u8> = Vec::with_capacity(6 * 32);
 
-        // grab conductor address from Custodian
-        encoded.extend(Custodian::conductor_address()?); // 32
+        // conductor address from Custodian
+        encoded.extend(conductor_address); // 32
 
         // sale id
         encoded.extend(self.id); // 32
@@ -467,10 +576,6 @@ impl Sale {
     }
 
     pub fn block_contributions(&mut self) {
-        msg!(
-            "contributions are blocked for sale {}",
-            hex::encode(&self.id)
-        );
         self.sale_token_mint = Pubkey::new_from_array([0u8; 32]);
         self.sale_token_ata = Pubkey::new_from_array([0u8; 32]);
         self.contributions_blocked = true;
@@ -1,7 +1,9 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::keccak};
 use borsh::{BorshDeserialize, BorshSerialize};
 use std::io::Write;
 
+use crate::{constants::SEED_PREFIX_POSTED_VAA, error::ContributorError};
+
 #[derive(AnchorDeserialize, AnchorSerialize)]
 pub struct PostMessageData {
     /// Unique nonce for this message
@@ -112,10 +114,9 @@ impl AnchorDeserialize for PostedVAAData {
             ));
         }
 
-        // We accept "vaa", "msg", or "msu" because it's convenient to read all of these as PostedVAAData
-        let expected: [&[u8]; 3] = [b"vaa", b"msg", b"msu"];
-        let magic: &[u8] = &buf[0..3];
-        if !expected.contains(&magic) {
+        // We only accept "vaa". Posted messages ("msg" and "msu") have the same layout, but
+        // they were never signed by the guardians.
+        if &buf[0..3] != b"vaa" {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
                 "Invalid VAA Message",
@@ -128,6 +129,163 @@ impl AnchorDeserialize for PostedVAAData {
     }
 }
 
+impl MessageData {
+    /// Hash of the VAA body, which the core bridge uses to derive the posted VAA account
+    pub fn hash(&self) -> [u8; 32] {
+        let mut body = Vec::with_capacity(51 + self.payload.len());
+        body.extend_from_slice(&self.vaa_time.to_be_bytes());
+        body.extend_from_slice(&self.nonce.to_be_bytes());
+        body.extend_from_slice(&self.emitter_chain.to_be_bytes());
+        body.extend_from_slice(&self.emitter_address);
+        body.extend_from_slice(&self.sequence.to_be_bytes());
+        body.push(self.consistency_level);
+        body.extend_from_slice(&self.payload);
+        keccak::hash(&body).to_bytes()
+    }
+}
+
 pub fn get_message_data<'info>(vaa_account: &AccountInfo<'info>) -> Result<MessageData> {
-    Ok(PostedVAAData::try_from_slice(&vaa_account.data.borrow())?.0)
+    let vaa = PostedVAAData::try_from_slice(&vaa_account.data.borrow())
+        .map_err(|_| ContributorError::InvalidWormholeMessageAccount)?;
+    Ok(vaa.0)
+}
+
+/// Like `get_message_data`, but also makes sure the account is the one the core bridge
+/// created after verifying the guardian signatures, i.e. the PDA derived from the body
+/// hash.
+///
+/// What a consistency level means depends on the emitter's chain (e.g. a number of
+/// block confirmations on EVM chains), so the caller passes the minimum it expects from
+/// that emitter. The guardians only sign a message once the emitter's chain reached
+/// the level the emitter asked for, which is what `min_consistency_level` is compared
+/// against.
+pub fn get_verified_message_data<'info>(
+    vaa_account: &AccountInfo<'info>,
+    wormhole: &Pubkey,
+    min_consistency_level: u8,
+) -> Result<MessageData> {
+    require!(
+        vaa_account.owner == wormhole,
+        ContributorError::InvalidWormholeMessageAccount
+    );
+
+    let msg = get_message_data(vaa_account)?;
+    let (expected_key, _) =
+        Pubkey::find_program_address(&[SEED_PREFIX_POSTED_VAA.as_bytes(), &msg.hash()], wormhole);
+    require!(
+        vaa_account.key() == expected_key,
+        ContributorError::InvalidWormholeMessageAccount
+    );
+    require!(
+        msg.consistency_level >= min_consistency_level,
+        ContributorError::InvalidConsistencyLevel
+    );
+    Ok(msg)
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    fn encode_posted(magic: &[u8], msg: &MessageData) -> Vec<u8> {
+        let mut data = magic.to_vec();
+        data.extend(msg.try_to_vec().unwrap());
+        data
+    }
+
+    fn make_message(consistency_level: u8) -> MessageData {
+        MessageData {
+            vaa_version: 1,
+            consistency_level,
+            vaa_time: 1654000000,
+            nonce: 0,
+            sequence: 1,
+            emitter_chain: 2,
+            emitter_address: [4u8; 32],
+            payload: vec![4u8; 33],
+            ..Default::default()
+        }
+    }
+
+    fn check_posted(
+        key: &Pubkey,
+        wormhole: &Pubkey,
+        mut data: Vec<u8>,
+        min_consistency_level: u8,
+    ) -> Result<MessageData> {
+        let mut lamports = 0;
+        let vaa_account = AccountInfo::new(
+            key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            wormhole,
+            false,
+            0,
+        );
+        get_verified_message_data(&vaa_account, wormhole, min_consistency_level)
+    }
+
+    #[test]
+    fn test_verified_vaa() -> Result<()> {
+        let wormhole = Pubkey::new_unique();
+        let msg = make_message(1);
+        let (key, _) = Pubkey::find_program_address(&[b"PostedVAA", &msg.hash()], &wormhole);
+
+        let verified = check_posted(&key, &wormhole, encode_posted(b"vaa", &msg), 1)?;
+        assert_eq!(verified.sequence, msg.sequence);
+        assert_eq!(verified.payload, msg.payload);
+        Ok(())
+    }
+
+    #[test]
+    fn test_posted_message_rejected() -> Result<()> {
+        let wormhole = Pubkey::new_unique();
+        let msg = make_message(1);
+        let (key, _) = Pubkey::find_program_address(&[b"PostedVAA", &msg.hash()], &wormhole);
+
+        // neither a posted message nor an unreliable one has been signed by the guardians
+        for magic in [b"msg", b"msu"] {
+            assert!(check_posted(&key, &wormhole, encode_posted(magic, &msg), 1).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_vaa_wrong_address_rejected() -> Result<()> {
+        let wormhole = Pubkey::new_unique();
+        let msg = make_message(1);
+
+        // a different body hash derives a different account
+        let mut other = make_message(1);
+        other.sequence = 2;
+        let (key, _) = Pubkey::find_program_address(&[b"PostedVAA", &other.hash()], &wormhole);
+        assert!(check_posted(&key, &wormhole, encode_posted(b"vaa", &msg), 1).is_err());
+
+        // same seeds, but not derived from the core bridge
+        let (key, _) = Pubkey::find_program_address(&[b"PostedVAA", &msg.hash()], &crate::ID);
+        assert!(check_posted(&key, &wormhole, encode_posted(b"vaa", &msg), 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_vaa_consistency_level_rejected() -> Result<()> {
+        let wormhole = Pubkey::new_unique();
+        let msg = make_message(1);
+        let (key, _) = Pubkey::find_program_address(&[b"PostedVAA", &msg.hash()], &wormhole);
+        assert!(check_posted(&key, &wormhole, encode_posted(b"vaa", &msg), 15).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_vaa_consistency_level_zero() -> Result<()> {
+        // some chains are final right away, so their emitters may ask for nothing more
+        let wormhole = Pubkey::new_unique();
+        let msg = make_message(0);
+        let (key, _) = Pubkey::find_program_address(&[b"PostedVAA", &msg.hash()], &wormhole);
+        assert!(check_posted(&key, &wormhole, encode_posted(b"vaa", &msg), 1).is_err());
+        check_posted(&key, &wormhole, encode_posted(b"vaa", &msg), 0)?;
+        Ok(())
+    }
 }
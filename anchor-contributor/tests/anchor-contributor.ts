@@ -14,10 +14,18 @@ import {
   redeemOnSolana,
   ChainId,
 } from "@certusone/wormhole-sdk";
-import { getOrCreateAssociatedTokenAccount, mintTo, getMint, createMint } from "@solana/spl-token";
+import {
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  getMint,
+  createMint,
+  NATIVE_MINT,
+  getAssociatedTokenAddress,
+  createSyncNativeInstruction,
+} from "@solana/spl-token";
 
 import { DummyConductor } from "./helpers/conductor";
-import { IccoContributor } from "./helpers/contributor";
+import { IccoContributor, SALE_ASSET_TOTAL_SIZE, SALE_BASE_SIZE } from "./helpers/contributor";
 import {
   deriveAddress,
   getBlockTime,
@@ -31,7 +39,10 @@ import { KycAuthority } from "./helpers/kyc";
 import {
   CONDUCTOR_ADDRESS,
   CONDUCTOR_CHAIN,
+  CONDUCTOR_CONSISTENCY_LEVEL,
   CORE_BRIDGE_ADDRESS,
+  GOVERNANCE_ADDRESS,
+  GOVERNANCE_CHAIN,
   KYC_PRIVATE_NEW,
   KYC_PRIVATE_OLD,
   KYC_PUBLIC_NEW,
@@ -39,7 +50,8 @@ import {
   TOKEN_BRIDGE_ADDRESS,
 } from "./helpers/consts";
 import { encodeAttestMeta, encodeTokenTransfer, parseTokenTransfer } from "./helpers/token-bridge";
-import { signAndEncodeVaa } from "./helpers/wormhole";
+import { encodeUpdateConductor, encodeUpgradeContract } from "./helpers/governance";
+import { parseVaa, signAndEncodeVaa } from "./helpers/wormhole";
 
 // be careful where you import this
 import { postVaaSolanaWithRetry } from "@certusone/wormhole-sdk";
@@ -179,7 +191,7 @@ describe("anchor-contributor", () => {
 
     it("Mint Accepted SPL Tokens to Buyer", async () => {
       // remaining token indices
-      const tokenIndices = [3, 5, 8, 13, 21, 34];
+      const tokenIndices = [3, 5, 8, 13, 21];
 
       for (let i = 0; i < tokenIndices.length; ++i) {
         const mint = await createMint(connection, orchestrator, orchestrator.publicKey, orchestrator.publicKey, 9);
@@ -203,6 +215,9 @@ describe("anchor-contributor", () => {
         expect(balance.toString()).to.equal(amount.toString());
       }
 
+      // buyers contribute SOL for the native mint, which is wrapped by the contributor
+      dummyConductor.addAcceptedToken(34, NATIVE_MINT);
+
       // make last index a non-existent token
       const lastTokenIndex = 55;
       dummyConductor.addAcceptedToken(
@@ -213,10 +228,47 @@ describe("anchor-contributor", () => {
   });
 
   describe("Custodian Setup", () => {
+    it("User Cannot Create Custodian", async () => {
+      // only the upgrade authority can decide which conductor to trust
+      let caughtError = false;
+      try {
+        const tx = await contributor.createCustodian(
+          buyer,
+          CONDUCTOR_CHAIN,
+          CONDUCTOR_ADDRESS,
+          CONDUCTOR_CONSISTENCY_LEVEL,
+          GOVERNANCE_CHAIN,
+          GOVERNANCE_ADDRESS
+        );
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "NotUpgradeAuthority");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
     it("Create Custodian", async () => {
-      const tx = await contributor.createCustodian(orchestrator);
+      const tx = await contributor.createCustodian(
+        orchestrator,
+        CONDUCTOR_CHAIN,
+        CONDUCTOR_ADDRESS,
+        CONDUCTOR_CONSISTENCY_LEVEL,
+        GOVERNANCE_CHAIN,
+        GOVERNANCE_ADDRESS
+      );
 
-      // nothing to verify
+      const custodianState = await contributor.getCustodian();
+      expect(custodianState.owner.equals(orchestrator.publicKey)).to.be.true;
+      expect(custodianState.conductorChain).to.equal(CONDUCTOR_CHAIN);
+      expect(Buffer.from(custodianState.conductorAddress).toString("hex")).to.equal(CONDUCTOR_ADDRESS);
+      expect(custodianState.conductorConsistencyLevel).to.equal(CONDUCTOR_CONSISTENCY_LEVEL);
+      expect(custodianState.governanceChain).to.equal(GOVERNANCE_CHAIN);
+      expect(Buffer.from(custodianState.governanceAddress).toString("hex")).to.equal(GOVERNANCE_ADDRESS);
+      expect(custodianState.wormhole.equals(CORE_BRIDGE_ADDRESS)).to.be.true;
+      expect(custodianState.tokenBridge.equals(TOKEN_BRIDGE_ADDRESS)).to.be.true;
     });
   });
 
@@ -278,6 +330,15 @@ describe("anchor-contributor", () => {
         const expectedSaleTokenAta = await getPdaAssociatedTokenAddress(saleState.saleTokenMint, contributor.custodian);
         expect(saleState.saleTokenAta.equals(expectedSaleTokenAta)).to.be.true;
 
+        // check emitted event
+        const events = await contributor.getEvents(tx);
+        expect(events.length).to.equal(1);
+        expect(events[0].name).to.equal("SaleInitialized");
+        expect(Uint8Array.from(events[0].data.saleId as number[])).to.deep.equal(saleId);
+        expect((events[0].data.saleTokenMint as web3.PublicKey).toString()).to.equal(dummyConductor.saleTokenOnSolana);
+        expect(events[0].data.numAccepted).to.equal(dummyConductor.acceptedTokens.length);
+        expect(events[0].data.contributionsBlocked).to.equal(false);
+
         // check totals
         const totals = saleState.totals as any[];
         const numAccepted = dummyConductor.acceptedTokens.length;
@@ -309,8 +370,7 @@ describe("anchor-contributor", () => {
         const tx = await contributor.initSale(orchestrator, dummyConductor.initSaleVaa);
         throw new Error(`should not happen: ${tx}`);
       } catch (e) {
-        // pda init should fail
-        caughtError = "programErrorStack" in e;
+        caughtError = verifyErrorMsg(e, "VaaAlreadyClaimed");
       }
 
       if (!caughtError) {
@@ -427,6 +487,7 @@ describe("anchor-contributor", () => {
       // contribute multiple times
       const saleId = dummyConductor.getSaleId();
       for (const tokenIndex of contributedTokenIndices) {
+        let buyerTotal = new BN(0);
         for (const amount of contributions.get(tokenIndex).map((value) => new BN(value))) {
           const tx = await contributor.contribute(
             buyer,
@@ -435,6 +496,16 @@ describe("anchor-contributor", () => {
             amount,
             await kyc.signContribution(saleId, tokenIndex, amount, buyer.publicKey)
           );
+
+          // check emitted event
+          buyerTotal = buyerTotal.add(amount);
+          const events = await contributor.getEvents(tx);
+          expect(events.length).to.equal(1);
+          expect(events[0].name).to.equal("Contributed");
+          expect((events[0].data.buyer as web3.PublicKey).equals(buyer.publicKey)).to.be.true;
+          expect(events[0].data.tokenIndex).to.equal(tokenIndex);
+          expect(events[0].data.amount.toString()).to.equal(amount.toString());
+          expect(events[0].data.total.toString()).to.equal(buyerTotal.toString());
         }
       }
 
@@ -498,8 +569,10 @@ describe("anchor-contributor", () => {
       }
     });
 
+    let kycAuthorityUpdatedVaa: Buffer = null;
+
     it("Orchestrator Updates Sale's KYC Authority with Signed VAA", async () => {
-      const kycAuthorityUpdatedVaa = dummyConductor.updateKycAuthority(await getBlockTime(connection), KYC_PUBLIC_NEW);
+      kycAuthorityUpdatedVaa = dummyConductor.updateKycAuthority(await getBlockTime(connection), KYC_PUBLIC_NEW);
       const tx = await contributor.updateKycAuthority(orchestrator, kycAuthorityUpdatedVaa);
 
       {
@@ -509,6 +582,20 @@ describe("anchor-contributor", () => {
       }
     });
 
+    it("Orchestrator Cannot Update Sale's KYC Authority Again with Same Signed VAA", async () => {
+      let caughtError = false;
+      try {
+        const tx = await contributor.updateKycAuthority(orchestrator, kycAuthorityUpdatedVaa);
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "VaaAlreadyClaimed");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
     it("User Cannot Contribute with Old KYC Authority", async () => {
       const saleId = dummyConductor.getSaleId();
       const tokenIndex = 2;
@@ -730,6 +817,35 @@ describe("anchor-contributor", () => {
       }
     });
 
+    it("Orchestrator Cannot Abort Sale With Posted Message", async () => {
+      const saleId = dummyConductor.getSaleId();
+
+      // the attest contributions message is owned by the core bridge, but it was never signed by the guardians
+      const program = contributor.program;
+      let caughtError = false;
+      try {
+        const tx = await program.methods
+          .abortSale()
+          .accounts({
+            custodian: contributor.custodian,
+            claim: web3.Keypair.generate().publicKey, // never derived because the message is rejected first
+            sale: contributor.deriveSaleAccount(saleId),
+            coreBridgeVaa: contributor.deriveAttestContributionsMessageAccount(saleId),
+            payer: orchestrator.publicKey,
+            systemProgram: web3.SystemProgram.programId,
+          })
+          .signers([orchestrator])
+          .rpc();
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "InvalidWormholeMessageAccount");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
     it("User Cannot Contribute After Sale Ended", async () => {
       const saleId = dummyConductor.getSaleId();
       const tokenIndex = 2;
@@ -1079,6 +1195,8 @@ describe("anchor-contributor", () => {
     // global contributions for test
     const contributions = new Map<number, string[]>();
     const totalContributions: BN[] = [];
+    const delegatedContribution = "1000000000";
+    const nativeContribution = "1000000000";
 
     it("Orchestrator Initialize Sale with Signed VAA", async () => {
       const startTime = 8 + (await getBlockTime(connection));
@@ -1093,7 +1211,10 @@ describe("anchor-contributor", () => {
         dummyConductor.tokenDecimals,
         KYC_PUBLIC_NEW
       );
-      const tx = await contributor.initSale(orchestrator, initSaleVaa);
+
+      // accepted tokens are added a few at a time
+      const mintsPerTx = 3;
+      const tx = await contributor.initSale(orchestrator, initSaleVaa, mintsPerTx);
 
       {
         const saleId = dummyConductor.getSaleId();
@@ -1112,6 +1233,7 @@ describe("anchor-contributor", () => {
         expect(Uint8Array.from(saleState.kycAuthority)).to.deep.equal(Buffer.from(KYC_PUBLIC_NEW, "hex"));
         expect(saleState.status).has.key("active");
         expect(saleState.contributionsBlocked).to.equal(false);
+        expect(saleState.initialized).to.equal(true);
 
         const expectedSaleTokenAta = await getPdaAssociatedTokenAddress(saleState.saleTokenMint, contributor.custodian);
         expect(saleState.saleTokenAta.equals(expectedSaleTokenAta)).to.be.true;
@@ -1119,6 +1241,7 @@ describe("anchor-contributor", () => {
         // check totals
         const totals = saleState.totals as any[];
         const numAccepted = dummyConductor.acceptedTokens.length;
+        expect(saleState.numAccepted).to.equal(numAccepted);
         expect(totals.length).to.equal(numAccepted);
 
         for (let i = 0; i < numAccepted; ++i) {
@@ -1131,6 +1254,14 @@ describe("anchor-contributor", () => {
           expect(total.allocations.toString()).to.equal("0");
           expect(total.excessContributions.toString()).to.equal("0");
         }
+
+        // the sale account is created without room for accepted tokens and grows with every batch
+        // of them, with the payer topping up its rent each time
+        const saleAcctInfo = await connection.getAccountInfo(contributor.deriveSaleAccount(saleId));
+        expect(saleAcctInfo.data.length).to.equal(8 + SALE_BASE_SIZE + SALE_ASSET_TOTAL_SIZE * numAccepted);
+        expect(saleAcctInfo.lamports).to.equal(
+          await connection.getMinimumBalanceForRentExemption(saleAcctInfo.data.length)
+        );
       }
     });
 
@@ -1166,14 +1297,140 @@ describe("anchor-contributor", () => {
       }
     });
 
+    it("Custodian Cannot Contribute on Behalf of User With Own KYC Signature", async () => {
+      const saleId = dummyConductor.getSaleId();
+      const tokenIndex = dummyConductor.acceptedTokens[1].index;
+      const amount = new BN("1000000000");
+
+      let caughtError = false;
+      try {
+        const tx = await contributor.contribute(
+          orchestrator,
+          saleId,
+          tokenIndex,
+          amount,
+          await kyc.signContribution(saleId, tokenIndex, amount, orchestrator.publicKey),
+          buyer.publicKey
+        );
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "InvalidKycSignature");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
+    it("Custodian Contributes on Behalf of User", async () => {
+      const saleId = dummyConductor.getSaleId();
+      const acceptedToken = dummyConductor.acceptedTokens[1];
+      const tokenIndex = acceptedToken.index;
+      const mint = hexToPublicKey(acceptedToken.address);
+      const amount = new BN(delegatedContribution);
+
+      // the orchestrator is the mint authority, so it can fund itself
+      const funderTokenAcct = await getOrCreateAssociatedTokenAccount(
+        connection,
+        orchestrator,
+        mint,
+        orchestrator.publicKey
+      );
+      await mintTo(connection, orchestrator, mint, funderTokenAcct.address, orchestrator, BigInt(amount.toString()));
+
+      const startingBalanceFunder = await getSplBalance(connection, mint, orchestrator.publicKey);
+      const startingBalanceBuyer = await getSplBalance(connection, mint, buyer.publicKey);
+
+      const tx = await contributor.contribute(
+        orchestrator,
+        saleId,
+        tokenIndex,
+        amount,
+        await kyc.signContribution(saleId, tokenIndex, amount, buyer.publicKey),
+        buyer.publicKey
+      );
+
+      // funds come from the custodian, but the contribution belongs to the buyer
+      const endingBalanceFunder = await getSplBalance(connection, mint, orchestrator.publicKey);
+      const endingBalanceBuyer = await getSplBalance(connection, mint, buyer.publicKey);
+      expect(startingBalanceFunder.sub(amount).toString()).to.equal(endingBalanceFunder.toString());
+      expect(startingBalanceBuyer.toString()).to.equal(endingBalanceBuyer.toString());
+
+      const buyerState = await contributor.getBuyer(saleId, buyer.publicKey);
+      const item = (buyerState.contributions as any[])[1];
+      expect(item.status).has.key("active");
+      expect(item.amount.toString()).to.equal(amount.toString());
+
+      const events = await contributor.getEvents(tx);
+      expect(events.length).to.equal(1);
+      expect(events[0].name).to.equal("Contributed");
+      expect((events[0].data.buyer as web3.PublicKey).equals(buyer.publicKey)).to.be.true;
+      expect((events[0].data.funder as web3.PublicKey).equals(orchestrator.publicKey)).to.be.true;
+    });
+
+    it("User Contributes SOL to Sale", async () => {
+      const saleId = dummyConductor.getSaleId();
+      const acceptedToken = dummyConductor.acceptedTokens[6];
+      const tokenIndex = acceptedToken.index;
+      expect(hexToPublicKey(acceptedToken.address).equals(NATIVE_MINT)).to.be.true;
+
+      const amount = new BN(nativeContribution);
+
+      const startingLamportsBuyer = new BN(await connection.getBalance(buyer.publicKey));
+      const startingBalanceCustodian = await getPdaSplBalance(connection, NATIVE_MINT, contributor.custodian);
+
+      const tx = await contributor.contribute(
+        buyer,
+        saleId,
+        tokenIndex,
+        amount,
+        await kyc.signContribution(saleId, tokenIndex, amount, buyer.publicKey)
+      );
+
+      // the buyer also pays for fees, but does not need a wrapped SOL account
+      const endingLamportsBuyer = new BN(await connection.getBalance(buyer.publicKey));
+      const endingBalanceCustodian = await getPdaSplBalance(connection, NATIVE_MINT, contributor.custodian);
+      expect(startingLamportsBuyer.sub(endingLamportsBuyer).gte(amount)).to.be.true;
+      expect(startingBalanceCustodian.add(amount).toString()).to.equal(endingBalanceCustodian.toString());
+
+      const buyerNativeAcct = await getAssociatedTokenAddress(NATIVE_MINT, buyer.publicKey);
+      expect(await connection.getAccountInfo(buyerNativeAcct)).is.null;
+
+      const buyerState = await contributor.getBuyer(saleId, buyer.publicKey);
+      const item = (buyerState.contributions as any[])[6];
+      expect(item.status).has.key("active");
+      expect(item.amount.toString()).to.equal(amount.toString());
+    });
+
+    let saleAbortedVaa: Buffer = null;
+
     it("Orchestrator Aborts Sale with Signed VAA", async () => {
-      const saleAbortedVaa = dummyConductor.abortSale(await getBlockTime(connection));
+      saleAbortedVaa = dummyConductor.abortSale(await getBlockTime(connection));
       const tx = await contributor.abortSale(orchestrator, saleAbortedVaa);
 
       {
         const saleId = dummyConductor.getSaleId();
         const saleState = await contributor.getSale(saleId);
         expect(saleState.status).has.key("aborted");
+
+        const events = await contributor.getEvents(tx);
+        expect(events.length).to.equal(1);
+        expect(events[0].name).to.equal("SaleAborted");
+        expect(Uint8Array.from(events[0].data.saleId as number[])).to.deep.equal(saleId);
+      }
+    });
+
+    it("Orchestrator Cannot Abort Sale Again with Same Signed VAA", async () => {
+      let caughtError = false;
+      try {
+        const tx = await contributor.abortSale(orchestrator, saleAbortedVaa);
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "VaaAlreadyClaimed");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
       }
     });
 
@@ -1199,6 +1456,22 @@ describe("anchor-contributor", () => {
       const sale = await contributor.getSale(saleId);
       const assets = sale.totals as any[];
 
+      // wrapped SOL is refunded as lamports, so whatever is in the buyer's wrapped SOL account stays there
+      const wrappedLamports = 1000;
+      const buyerNativeAcct = await getOrCreateAssociatedTokenAccount(connection, buyer, NATIVE_MINT, buyer.publicKey);
+      await web3.sendAndConfirmTransaction(
+        connection,
+        new web3.Transaction().add(
+          web3.SystemProgram.transfer({
+            fromPubkey: buyer.publicKey,
+            toPubkey: buyerNativeAcct.address,
+            lamports: wrappedLamports,
+          }),
+          createSyncNativeInstruction(buyerNativeAcct.address)
+        ),
+        [buyer]
+      );
+
       const startingBalanceBuyer = await Promise.all(
         assets.map(async (asset) => {
           return getSplBalance(connection, asset.mint, buyer.publicKey);
@@ -1209,6 +1482,7 @@ describe("anchor-contributor", () => {
           return getPdaSplBalance(connection, asset.mint, contributor.custodian);
         })
       );
+      const startingLamportsBuyer = new BN(await connection.getBalance(buyer.publicKey));
 
       const tx = await contributor.claimRefunds(buyer, saleId);
 
@@ -1225,12 +1499,12 @@ describe("anchor-contributor", () => {
 
       const expectedRefundAmounts = [
         totalContributions[0],
-        new BN(0),
+        new BN(delegatedContribution),
         new BN(0),
         totalContributions[1],
         new BN(0),
         new BN(0),
-        new BN(0),
+        new BN(nativeContribution),
         new BN(0),
       ];
       const numExpected = expectedRefundAmounts.length;
@@ -1244,13 +1518,36 @@ describe("anchor-contributor", () => {
       for (let i = 0; i < numExpected; ++i) {
         let refund = expectedRefundAmounts[i];
 
-        expect(startingBalanceBuyer[i].add(refund).toString()).to.equal(endingBalanceBuyer[i].toString());
+        if (assets[i].mint.equals(NATIVE_MINT)) {
+          // wrapped SOL is refunded as lamports and the temporary account's rent is returned
+          const endingLamportsBuyer = new BN(await connection.getBalance(buyer.publicKey));
+          expect(endingLamportsBuyer.sub(startingLamportsBuyer).toString()).to.equal(refund.toString());
+          expect(startingBalanceBuyer[i].toString()).to.equal(wrappedLamports.toString());
+          expect(endingBalanceBuyer[i].toString()).to.equal(wrappedLamports.toString());
+
+          const unwrapAcct = await connection.getAccountInfo(contributor.deriveUnwrapAccount(buyer.publicKey));
+          expect(unwrapAcct).to.be.null;
+        } else {
+          expect(startingBalanceBuyer[i].add(refund).toString()).to.equal(endingBalanceBuyer[i].toString());
+        }
         expect(startingBalanceCustodian[i].sub(refund).toString()).to.equal(endingBalanceCustodian[i].toString());
 
         const item = totals[i];
         expect(item.status).has.key("refundClaimed");
         expect(item.excess.toString()).to.equal(refund.toString());
       }
+
+      // only non-zero refunds are emitted
+      const events = await contributor.getEvents(tx);
+      const expectedEvents = expectedRefundAmounts
+        .map((refund, i) => ({ refund, tokenIndex: assets[i].tokenIndex }))
+        .filter(({ refund }) => !refund.eq(new BN(0)));
+      expect(events.length).to.equal(expectedEvents.length);
+      for (let i = 0; i < expectedEvents.length; ++i) {
+        expect(events[i].name).to.equal("RefundClaimed");
+        expect(events[i].data.tokenIndex).to.equal(expectedEvents[i].tokenIndex);
+        expect(events[i].data.amount.toString()).to.equal(expectedEvents[i].refund.toString());
+      }
     });
 
     it("User Cannot Claim Refund Again", async () => {
@@ -1286,7 +1583,8 @@ describe("anchor-contributor", () => {
         7,
         KYC_PUBLIC_NEW
       );
-      const tx = await contributor.initSale(orchestrator, initSaleVaa);
+      const mintsPerTx = 5;
+      const tx = await contributor.initSale(orchestrator, initSaleVaa, mintsPerTx);
 
       {
         const saleId = dummyConductor.getSaleId();
@@ -1311,9 +1609,17 @@ describe("anchor-contributor", () => {
         // saleTokenAta is zero address
         expect(saleState.saleTokenAta.equals(web3.PublicKey.default)).to.be.true;
 
+        // indexers are told that contributions are blocked
+        const events = await contributor.getEvents(tx);
+        expect(events.length).to.equal(1);
+        expect(events[0].name).to.equal("SaleInitialized");
+        expect(events[0].data.contributionsBlocked).to.equal(true);
+
         // check totals
         const totals = saleState.totals as any[];
         const numAccepted = dummyConductor.acceptedTokens.length;
+        expect(saleState.numAccepted).to.equal(numAccepted);
+        expect(events[0].data.numAccepted).to.equal(numAccepted);
         expect(totals.length).to.equal(numAccepted);
 
         for (let i = 0; i < numAccepted; ++i) {
@@ -1329,6 +1635,22 @@ describe("anchor-contributor", () => {
       }
     });
 
+    it("Orchestrator Cannot Add Accepted Tokens After Sale Is Initialized", async () => {
+      const acceptedMints = dummyConductor.acceptedTokens.slice(0, 1).map((token) => hexToPublicKey(token.address));
+
+      let caughtError = false;
+      try {
+        const tx = await contributor.addAcceptedTokens(orchestrator, dummyConductor.initSaleVaa, acceptedMints);
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "SaleAlreadyInitialized");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
     it("User Cannot Contribute to Blocked Sale", async () => {
       // wait for sale to start here
       const saleStart = dummyConductor.saleStart;
@@ -1974,6 +2296,413 @@ describe("anchor-contributor", () => {
       expect(buyerState.allocation.amount.toString()).to.equal(expectedAllocation.toString());
     });
   });
+
+  describe("Conduct Successful Sale (More Than Eight Accepted Tokens)", () => {
+    // global contributions for test
+    const contributions = new Map<number, string[]>();
+    const totalContributions: BN[] = [];
+
+    const saleTokenDecimals = 7;
+
+    // the first accepted tokens are passed to init_sale, the rest to add_accepted_tokens
+    const mintsPerTx = 4;
+
+    it("Mint More Accepted SPL Tokens to Buyer", async () => {
+      const saleTokenMint = await createMint(
+        connection,
+        orchestrator,
+        orchestrator.publicKey,
+        orchestrator.publicKey,
+        saleTokenDecimals
+      );
+      dummyConductor.saveSaleTokenMint(saleTokenMint);
+
+      // bring the accepted tokens up to the contributor's limit
+      const tokenIndices = [89, 144, 233];
+
+      for (let i = 0; i < tokenIndices.length; ++i) {
+        const mint = await createMint(connection, orchestrator, orchestrator.publicKey, orchestrator.publicKey, 9);
+        dummyConductor.addAcceptedToken(tokenIndices.at(i), mint);
+
+        const tokenAccount = await getOrCreateAssociatedTokenAccount(connection, buyer, mint, buyer.publicKey);
+
+        let amount = new BN("200000000000");
+        await mintTo(connection, orchestrator, mint, tokenAccount.address, orchestrator, BigInt(amount.toString()));
+      }
+      expect(dummyConductor.acceptedTokens.length).to.equal(11);
+    });
+
+    it("Orchestrator Cannot Initialize Sale with Too Many Accepted Tokens", async () => {
+      // one more accepted token than the claim instructions can take
+      dummyConductor.addAcceptedToken(0, orchestrator.publicKey);
+
+      const startTime = 8 + (await getBlockTime(connection));
+      const initSaleVaa = dummyConductor.createSale(
+        startTime,
+        8,
+        12,
+        dummyConductor.saleTokenOnSolana,
+        CHAIN_ID_SOLANA,
+        saleTokenDecimals,
+        KYC_PUBLIC_NEW
+      );
+      dummyConductor.acceptedTokens.pop();
+
+      let caughtError = false;
+      try {
+        const tx = await contributor.initSale(orchestrator, initSaleVaa, mintsPerTx);
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "TooManyAcceptedTokens");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
+    it("Orchestrator Initializes Sale with Some Accepted Tokens", async () => {
+      const startTime = 12 + (await getBlockTime(connection));
+      const duration = 8; // seconds after sale starts
+      const lockPeriod = 12; // seconds after sale ended
+      const initSaleVaa = dummyConductor.createSale(
+        startTime,
+        duration,
+        lockPeriod,
+        dummyConductor.saleTokenOnSolana,
+        CHAIN_ID_SOLANA,
+        saleTokenDecimals,
+        KYC_PUBLIC_NEW
+      );
+      const addRemainingMints = false;
+      const tx = await contributor.initSale(orchestrator, initSaleVaa, mintsPerTx, addRemainingMints);
+
+      const saleState = await contributor.getSale(dummyConductor.getSaleId());
+      expect(saleState.numAccepted).to.equal(dummyConductor.acceptedTokens.length);
+      expect((saleState.totals as any[]).length).to.equal(mintsPerTx);
+      expect(saleState.initialized).to.equal(false);
+      expect(Buffer.from(saleState.initVaaHash).equals(parseVaa(initSaleVaa).hash)).to.be.true;
+    });
+
+    it("User Cannot Contribute Before Every Accepted Token Is Added", async () => {
+      const saleId = dummyConductor.getSaleId();
+      const tokenIndex = dummyConductor.acceptedTokens[0].index;
+      const amount = new BN("1000000000");
+
+      let caughtError = false;
+      try {
+        const tx = await contributor.contribute(
+          buyer,
+          saleId,
+          tokenIndex,
+          amount,
+          await kyc.signContribution(saleId, tokenIndex, amount, buyer.publicKey)
+        );
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "SaleNotInitialized");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
+    it("Orchestrator Cannot Add Accepted Tokens with Another Signed VAA", async () => {
+      // same sale init payload, but a different VAA
+      const initSaleVaa = dummyConductor.initSaleVaa;
+      const otherVaa = signAndEncodeVaa(
+        await getBlockTime(connection),
+        dummyConductor.nonce,
+        dummyConductor.chainId,
+        dummyConductor.address,
+        ++dummyConductor.wormholeSequence,
+        parseVaa(initSaleVaa).data
+      );
+      await contributor.postVaa(orchestrator, otherVaa);
+
+      const acceptedMints = dummyConductor.acceptedTokens
+        .slice(mintsPerTx, 2 * mintsPerTx)
+        .map((token) => hexToPublicKey(token.address));
+
+      let caughtError = false;
+      try {
+        const tx = await contributor.addAcceptedTokens(orchestrator, otherVaa, acceptedMints);
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "InvalidSaleInitVaa");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
+    it("Orchestrator Adds Remaining Accepted Tokens", async () => {
+      const acceptedMints = dummyConductor.acceptedTokens.map((token) => hexToPublicKey(token.address));
+      for (let start = mintsPerTx; start < acceptedMints.length; start += mintsPerTx) {
+        await contributor.addAcceptedTokens(
+          orchestrator,
+          dummyConductor.initSaleVaa,
+          acceptedMints.slice(start, start + mintsPerTx)
+        );
+      }
+
+      const saleId = dummyConductor.getSaleId();
+      const saleState = await contributor.getSale(saleId);
+      expect(saleState.initialized).to.equal(true);
+
+      const totals = saleState.totals as any[];
+      const numAccepted = dummyConductor.acceptedTokens.length;
+      expect(totals.length).to.equal(numAccepted);
+      for (let i = 0; i < numAccepted; ++i) {
+        expect(totals[i].tokenIndex).to.equal(dummyConductor.acceptedTokens[i].index);
+        expect(tryNativeToHexString(totals[i].mint.toString(), CHAIN_ID_SOLANA)).to.equal(
+          dummyConductor.acceptedTokens[i].address
+        );
+      }
+
+      const saleAcctInfo = await connection.getAccountInfo(contributor.deriveSaleAccount(saleId));
+      expect(saleAcctInfo.data.length).to.equal(8 + SALE_BASE_SIZE + SALE_ASSET_TOTAL_SIZE * numAccepted);
+    });
+
+    it("User Contributes to Sale", async () => {
+      // wait for sale to start here
+      const saleStart = dummyConductor.saleStart;
+      await waitUntilBlock(connection, saleStart);
+
+      // contribute to one of the first accepted tokens and one that was added later
+      const acceptedTokens = dummyConductor.acceptedTokens;
+      const contributedTokenIndices = [acceptedTokens[0].index, acceptedTokens[9].index];
+      contributions.set(contributedTokenIndices[0], ["300000000"]);
+      contributions.set(contributedTokenIndices[1], ["500000000", "700000000"]);
+
+      contributedTokenIndices.forEach((tokenIndex) => {
+        const amounts = contributions.get(tokenIndex);
+        totalContributions.push(amounts.map((x) => new BN(x)).reduce((prev, curr) => prev.add(curr)));
+      });
+
+      const saleId = dummyConductor.getSaleId();
+      for (const tokenIndex of contributedTokenIndices) {
+        for (const amount of contributions.get(tokenIndex).map((value) => new BN(value))) {
+          const tx = await contributor.contribute(
+            buyer,
+            saleId,
+            tokenIndex,
+            amount,
+            await kyc.signContribution(saleId, tokenIndex, amount, buyer.publicKey)
+          );
+        }
+      }
+
+      const buyerState = await contributor.getBuyer(saleId, buyer.publicKey);
+      const buyerContributions = buyerState.contributions as any[];
+      expect(buyerContributions.length).to.equal(acceptedTokens.length);
+      expect(buyerContributions[0].amount.toString()).to.equal(totalContributions[0].toString());
+      expect(buyerContributions[9].amount.toString()).to.equal(totalContributions[1].toString());
+    });
+
+    it("Orchestrator Attests Contributions", async () => {
+      const saleId = dummyConductor.getSaleId();
+
+      // wait for sale to end here
+      const saleEnd = dummyConductor.saleEnd;
+      await waitUntilBlock(connection, saleEnd);
+      const tx = await contributor.attestContributions(orchestrator, saleId);
+
+      await connection.confirmTransaction(tx);
+      const vaaAccountInfo = await connection.getAccountInfo(
+        contributor.deriveAttestContributionsMessageAccount(saleId),
+        "confirmed"
+      );
+      const payload = vaaAccountInfo.data.subarray(95); // 95 is where the payload starts
+      expect(payload.readUint8(67)).to.equal(dummyConductor.acceptedTokens.length);
+    });
+
+    it("Orchestrator Seals Sale with Signed VAA", async () => {
+      const saleSealedVaa = dummyConductor.sealSale(await getBlockTime(connection), contributions);
+      await dummyConductor.redeemAllocationsOnSolana(connection, orchestrator, contributor.custodian);
+
+      const tx = await contributor.sealSale(orchestrator, saleSealedVaa);
+
+      const saleState = await contributor.getSale(dummyConductor.getSaleId());
+      expect(saleState.status).has.key("sealed");
+
+      const totals = saleState.totals as any[];
+      const allocations = dummyConductor.allocations;
+      expect(totals.length).to.equal(allocations.length);
+      for (let i = 0; i < totals.length; ++i) {
+        expect(totals[i].excessContributions.toString()).to.equal(allocations[i].excessContribution);
+      }
+    });
+
+    it("User Claims Contribution Excess From Sale", async () => {
+      const saleId = dummyConductor.getSaleId();
+      const sale = await contributor.getSale(saleId);
+      const assets = sale.totals as any[];
+
+      const startingBalanceBuyer = await Promise.all(
+        assets.map(async (asset) => {
+          return getSplBalance(connection, asset.mint, buyer.publicKey);
+        })
+      );
+
+      const tx = await contributor.claimExcesses(buyer, saleId);
+
+      const endingBalanceBuyer = await Promise.all(
+        assets.map(async (asset) => {
+          return getSplBalance(connection, asset.mint, buyer.publicKey);
+        })
+      );
+
+      const expectedExcessAmounts = dummyConductor.allocations.map((item) => new BN(item.excessContribution));
+      expect(expectedExcessAmounts[9].gt(new BN(0))).to.be.true;
+
+      const buyerState = await contributor.getBuyer(saleId, buyer.publicKey);
+      const totals = buyerState.contributions as any[];
+      for (let i = 0; i < expectedExcessAmounts.length; ++i) {
+        const excess = expectedExcessAmounts[i];
+        expect(startingBalanceBuyer[i].add(excess).toString()).to.equal(endingBalanceBuyer[i].toString());
+        expect(totals[i].status).has.key("excessClaimed");
+      }
+    });
+
+    it("User Claims Allocations From Sale", async () => {
+      const saleId = dummyConductor.getSaleId();
+
+      // wait until unlock
+      const saleUnlock = dummyConductor.saleUnlock;
+      await waitUntilBlock(connection, saleUnlock);
+
+      const tx = await contributor.claimAllocation(buyer, saleId);
+
+      const buyerState = await contributor.getBuyer(saleId, buyer.publicKey);
+      expect(buyerState.allocation.claimed).to.be.true;
+
+      const allocationDivisor = new BN(dummyConductor.getAllocationMultiplier());
+      const expectedAllocation = dummyConductor.allocations
+        .map((item) => new BN(item.allocation))
+        .reduce((prev, curr) => prev.add(curr))
+        .div(allocationDivisor);
+      expect(buyerState.allocation.amount.toString()).to.equal(expectedAllocation.toString());
+    });
+  });
+
+  describe("Governance", () => {
+    // governance vaas need a new sequence each time
+    let governanceSequence = 0;
+
+    const newConductorChain = CHAIN_ID_ETH as number;
+    const newConductorAddress = tryNativeToHexString("0x0290FB167208Af455bB137780163b7B7a9a10C17", CHAIN_ID_ETH);
+    let updateConductorVaa: Buffer = null;
+
+    it("Orchestrator Cannot Submit Governance From Conductor", async () => {
+      const governanceVaa = signAndEncodeVaa(
+        await getBlockTime(connection),
+        0,
+        CONDUCTOR_CHAIN,
+        Buffer.from(CONDUCTOR_ADDRESS, "hex"),
+        ++dummyConductor.wormholeSequence,
+        encodeUpdateConductor(CHAIN_ID_SOLANA, newConductorChain, newConductorAddress)
+      );
+
+      let caughtError = false;
+      try {
+        const tx = await contributor.submitGovernance(orchestrator, governanceVaa);
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "InvalidGovernanceChain");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
+    it("Orchestrator Cannot Submit Governance for Another Chain", async () => {
+      const governanceVaa = signAndEncodeVaa(
+        await getBlockTime(connection),
+        0,
+        GOVERNANCE_CHAIN,
+        Buffer.from(GOVERNANCE_ADDRESS, "hex"),
+        ++governanceSequence,
+        encodeUpdateConductor(CHAIN_ID_ETH, newConductorChain, newConductorAddress)
+      );
+
+      let caughtError = false;
+      try {
+        const tx = await contributor.submitGovernance(orchestrator, governanceVaa);
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "InvalidGovernanceTargetChain");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
+    it("Orchestrator Cannot Upgrade Contributors on Every Chain", async () => {
+      // only a conductor update may be addressed to chain 0
+      const governanceVaa = signAndEncodeVaa(
+        await getBlockTime(connection),
+        0,
+        GOVERNANCE_CHAIN,
+        Buffer.from(GOVERNANCE_ADDRESS, "hex"),
+        ++governanceSequence,
+        encodeUpgradeContract(0, web3.Keypair.generate().publicKey)
+      );
+
+      let caughtError = false;
+      try {
+        const tx = await contributor.submitGovernance(orchestrator, governanceVaa);
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "InvalidGovernanceTargetChain");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+
+    it("Orchestrator Updates Conductor", async () => {
+      updateConductorVaa = signAndEncodeVaa(
+        await getBlockTime(connection),
+        0,
+        GOVERNANCE_CHAIN,
+        Buffer.from(GOVERNANCE_ADDRESS, "hex"),
+        ++governanceSequence,
+        encodeUpdateConductor(CHAIN_ID_SOLANA, newConductorChain, newConductorAddress)
+      );
+
+      const tx = await contributor.submitGovernance(orchestrator, updateConductorVaa);
+
+      const custodianState = await contributor.getCustodian();
+      expect(custodianState.conductorChain).to.equal(newConductorChain);
+      expect(Buffer.from(custodianState.conductorAddress).toString("hex")).to.equal(newConductorAddress);
+
+      const events = await contributor.getEvents(tx);
+      expect(events.length).to.equal(1);
+      expect(events[0].name).to.equal("ConductorUpdated");
+      expect(events[0].data.conductorChain).to.equal(newConductorChain);
+      expect(Buffer.from(events[0].data.conductorAddress as number[]).toString("hex")).to.equal(newConductorAddress);
+    });
+
+    it("Orchestrator Cannot Submit Same Governance VAA Again", async () => {
+      let caughtError = false;
+      try {
+        const tx = await contributor.submitGovernance(orchestrator, updateConductorVaa);
+        throw new Error(`should not happen: ${tx}`);
+      } catch (e) {
+        caughtError = verifyErrorMsg(e, "VaaAlreadyClaimed");
+      }
+
+      if (!caughtError) {
+        throw new Error("did not catch expected error");
+      }
+    });
+  });
 });
 
 async function waitUntilBlock(connection: web3.Connection, expiration: number) {
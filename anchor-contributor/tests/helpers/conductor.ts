@@ -7,7 +7,6 @@ import { signAndEncodeVaa } from "./wormhole";
 import { SolanaAcceptedToken } from "./types";
 
 // sale struct info
-export const MAX_ACCEPTED_TOKENS = 8;
 const NUM_BYTES_ACCEPTED_TOKEN = 33;
 const NUM_BYTES_ALLOCATION = 65;
 
@@ -197,6 +196,9 @@ export class DummyConductor {
     "8000000000",
     "13000000000",
     "21000000000",
+    "34000000000",
+    "55000000000",
+    "89000000000",
   ];
 
   // wormhole nonce
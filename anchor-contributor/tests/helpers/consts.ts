@@ -7,6 +7,9 @@ export const TOKEN_BRIDGE_ADDRESS = new web3.PublicKey(process.env.TOKEN_BRIDGE_
 // contributor
 export const CONDUCTOR_CHAIN: number = parseInt(process.env.CONDUCTOR_CHAIN);
 export const CONDUCTOR_ADDRESS: string = process.env.CONDUCTOR_ADDRESS;
+export const CONDUCTOR_CONSISTENCY_LEVEL: number = parseInt(process.env.CONDUCTOR_CONSISTENCY_LEVEL);
+export const GOVERNANCE_CHAIN: number = parseInt(process.env.GOVERNANCE_CHAIN);
+export const GOVERNANCE_ADDRESS: string = process.env.GOVERNANCE_ADDRESS;
 
 // kyc
 export const KYC_PRIVATE_OLD: string = "b0057716d5917badaf911b193b12b910811c1497b5bada8d7711f758981c3773";
@@ -1,9 +1,10 @@
-import { BN, Program, web3 } from "@project-serum/anchor";
+import { BN, Event, EventParser, Program, web3 } from "@project-serum/anchor";
 import { AnchorContributor } from "../../target/types/anchor_contributor";
 import {
   getAssociatedTokenAddress,
   getMint,
   getOrCreateAssociatedTokenAccount,
+  NATIVE_MINT,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import * as byteify from "byteify";
@@ -19,6 +20,16 @@ const INDEX_SALE_INIT_ACCEPTED_TOKENS_START = 132;
 const ACCEPTED_TOKEN_NUM_BYTES = 33;
 const INDEX_ACCEPTED_TOKEN_ADDRESS = 1;
 
+const INDEX_GOVERNANCE_ACTION = 32;
+const INDEX_GOVERNANCE_PAYLOAD = 35;
+const GOVERNANCE_ACTION_UPGRADE_CONTRACT = 1;
+
+// sale account size (excluding the discriminator) without any accepted tokens, and what each one adds
+export const SALE_BASE_SIZE = 248;
+export const SALE_ASSET_TOTAL_SIZE = 58;
+
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+
 export class IccoContributor {
   program: Program<AnchorContributor>;
   wormhole: web3.PublicKey;
@@ -41,20 +52,47 @@ export class IccoContributor {
     this.custodian = this.deriveCustodianAccount();
   }
 
-  async createCustodian(payer: web3.Keypair) {
+  // payer must be the program's upgrade authority
+  async createCustodian(
+    payer: web3.Keypair,
+    conductorChain: number,
+    conductorAddress: string,
+    conductorConsistencyLevel: number,
+    governanceChain: number,
+    governanceAddress: string
+  ) {
     const program = this.program;
 
     return program.methods
-      .createCustodian()
+      .createCustodian(
+        conductorChain,
+        [...Buffer.from(conductorAddress, "hex")],
+        conductorConsistencyLevel,
+        governanceChain,
+        [...Buffer.from(governanceAddress, "hex")],
+        this.wormhole,
+        this.tokenBridge
+      )
       .accounts({
         payer: payer.publicKey,
         custodian: this.custodian,
+        program: program.programId,
+        programData: this.deriveProgramDataAccount(),
         systemProgram: web3.SystemProgram.programId,
       })
+      .signers([payer])
       .rpc();
   }
 
-  async initSale(payer: web3.Keypair, initSaleVaa: Buffer): Promise<string> {
+  // accepted token mints that do not fit in the init_sale transaction (more than mintsPerTx)
+  // are added with add_accepted_tokens, unless addRemainingMints is false. This returns the
+  // init_sale transaction
+  async initSale(
+    payer: web3.Keypair,
+    initSaleVaa: Buffer,
+    mintsPerTx?: number,
+    addRemainingMints: boolean = true
+  ): Promise<string> {
     const program = this.program;
     const connection = program.provider.connection;
 
@@ -63,6 +101,7 @@ export class IccoContributor {
     // first post signed vaa to wormhole
     await this.postVaa(payer, initSaleVaa);
     const coreBridgeVaa = this.deriveSignedVaaAccount(initSaleVaa);
+    const claim = this.deriveClaimAccount(initSaleVaa);
 
     const saleId = parseSaleId(initSaleVaa);
     const sale = this.deriveSaleAccount(saleId);
@@ -90,12 +129,12 @@ export class IccoContributor {
     });
 
     const numAccepted = payload.at(INDEX_SALE_INIT_ACCEPTED_TOKENS_START);
-    const remainingAccounts: web3.AccountMeta[] = [];
+    const acceptedMints: web3.PublicKey[] = [];
     for (let i = 0; i < numAccepted; ++i) {
       const start =
         INDEX_SALE_INIT_ACCEPTED_TOKENS_START + 1 + ACCEPTED_TOKEN_NUM_BYTES * i + INDEX_ACCEPTED_TOKEN_ADDRESS;
       const mint = new web3.PublicKey(payload.subarray(start, start + 32));
-      remainingAccounts.push(makeReadOnlyAccountMeta(mint));
+      acceptedMints.push(mint);
 
       // create ATAs
       await getOrCreateAssociatedTokenAccount(connection, payer, mint, custodian, true).catch((_) => {
@@ -103,10 +142,12 @@ export class IccoContributor {
       });
     }
 
-    return program.methods
+    const batchSize = mintsPerTx === undefined ? numAccepted : mintsPerTx;
+    const tx = await program.methods
       .initSale()
       .accounts({
         custodian,
+        claim,
         sale,
         coreBridgeVaa,
         saleTokenMint,
@@ -114,7 +155,41 @@ export class IccoContributor {
         tokenBridge: this.tokenBridge,
         systemProgram: web3.SystemProgram.programId,
       })
-      .remainingAccounts(remainingAccounts)
+      .remainingAccounts(acceptedMints.slice(0, batchSize).map((mint) => makeReadOnlyAccountMeta(mint)))
+      .rpc();
+
+    if (!addRemainingMints) {
+      return tx;
+    }
+    for (let start = batchSize; start < numAccepted; start += batchSize) {
+      await this.addAcceptedTokens(payer, initSaleVaa, acceptedMints.slice(start, start + batchSize));
+    }
+    return tx;
+  }
+
+  // the signed vaa has already been posted by initSale
+  async addAcceptedTokens(
+    payer: web3.Keypair,
+    initSaleVaa: Buffer,
+    acceptedMints: web3.PublicKey[]
+  ): Promise<string> {
+    const program = this.program;
+
+    const coreBridgeVaa = this.deriveSignedVaaAccount(initSaleVaa);
+    const saleId = parseSaleId(initSaleVaa);
+    const sale = this.deriveSaleAccount(saleId);
+
+    return program.methods
+      .addAcceptedTokens()
+      .accounts({
+        custodian: this.custodian,
+        sale,
+        coreBridgeVaa,
+        payer: payer.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([payer])
+      .remainingAccounts(acceptedMints.map((mint) => makeReadOnlyAccountMeta(mint)))
       .rpc();
   }
 
@@ -123,7 +198,8 @@ export class IccoContributor {
     saleId: Buffer,
     tokenIndex: number,
     amount: BN,
-    kycSignature: Buffer
+    kycSignature: Buffer,
+    beneficiary: web3.PublicKey = payer.publicKey
   ): Promise<string> {
     // first find mint
     const state = await this.getSale(saleId);
@@ -142,22 +218,26 @@ export class IccoContributor {
 
     const custodian = this.custodian;
 
-    const buyer = this.deriveBuyerAccount(saleId, payer.publicKey);
+    // the buyer account belongs to the beneficiary, but the payer funds the contribution
+    const buyer = this.deriveBuyerAccount(saleId, beneficiary);
     const sale = this.deriveSaleAccount(saleId);
 
-    const buyerTokenAcct = await getOrCreateAssociatedTokenAccount(connection, payer, acceptedMint, payer.publicKey)
-      .catch((_) => {
-        // illegimate accepted token... don't throw and derive address anyway
-        return null;
-      })
-      .then(async (account) => {
-        if (account != null) {
-          return new web3.PublicKey(account.address);
-        }
-
-        // we still want to generate an address here
-        return getAssociatedTokenAddress(acceptedMint, payer.publicKey);
-      });
+    // lamports are contributed for the native mint, so the payer does not need a wrapped SOL account
+    const buyerTokenAcct = acceptedMint.equals(NATIVE_MINT)
+      ? await getAssociatedTokenAddress(acceptedMint, payer.publicKey)
+      : await getOrCreateAssociatedTokenAccount(connection, payer, acceptedMint, payer.publicKey)
+          .catch((_) => {
+            // illegimate accepted token... don't throw and derive address anyway
+            return null;
+          })
+          .then(async (account) => {
+            if (account != null) {
+              return new web3.PublicKey(account.address);
+            }
+
+            // we still want to generate an address here
+            return getAssociatedTokenAddress(acceptedMint, payer.publicKey);
+          });
     const custodianTokenAcct = await getPdaAssociatedTokenAddress(acceptedMint, custodian);
 
     return program.methods
@@ -166,6 +246,7 @@ export class IccoContributor {
         custodian,
         sale,
         buyer,
+        beneficiary,
         owner: payer.publicKey,
         systemProgram: web3.SystemProgram.programId,
         buyerTokenAcct,
@@ -195,6 +276,7 @@ export class IccoContributor {
     return program.methods
       .attestContributions()
       .accounts({
+        custodian: this.custodian,
         sale,
         payer: payer.publicKey,
         systemProgram: web3.SystemProgram.programId,
@@ -223,6 +305,7 @@ export class IccoContributor {
     // first post signed vaa to wormhole
     await this.postVaa(payer, saleSealedVaa);
     const coreBridgeVaa = this.deriveSignedVaaAccount(saleSealedVaa);
+    const claim = this.deriveClaimAccount(saleSealedVaa);
 
     const sale = this.deriveSaleAccount(saleId);
     const custodianSaleTokenAcct = await getPdaAssociatedTokenAddress(saleTokenMint, custodian);
@@ -246,11 +329,14 @@ export class IccoContributor {
       .sealSale()
       .accounts({
         custodian,
+        claim,
         sale,
         coreBridgeVaa,
         custodianSaleTokenAcct,
+        payer: payer.publicKey,
         systemProgram: web3.SystemProgram.programId,
       })
+      .signers([payer])
       .remainingAccounts(remainingAccounts)
       .rpc();
   }
@@ -335,6 +421,7 @@ export class IccoContributor {
     // first post signed vaa to wormhole
     await this.postVaa(payer, saleAbortedVaa);
     const coreBridgeVaa = this.deriveSignedVaaAccount(saleAbortedVaa);
+    const claim = this.deriveClaimAccount(saleAbortedVaa);
 
     const saleId = await parseSaleId(saleAbortedVaa);
     const sale = this.deriveSaleAccount(saleId);
@@ -343,10 +430,13 @@ export class IccoContributor {
       .abortSale()
       .accounts({
         custodian,
+        claim,
         sale,
         coreBridgeVaa,
+        payer: payer.publicKey,
         systemProgram: web3.SystemProgram.programId,
       })
+      .signers([payer])
       .rpc();
   }
 
@@ -357,6 +447,7 @@ export class IccoContributor {
     // first post signed vaa to wormhole
     await this.postVaa(payer, authorityUpdatedVaa);
     const coreBridgeVaa = this.deriveSignedVaaAccount(authorityUpdatedVaa);
+    const claim = this.deriveClaimAccount(authorityUpdatedVaa);
 
     const saleId = await parseSaleId(authorityUpdatedVaa);
     const sale = this.deriveSaleAccount(saleId);
@@ -365,10 +456,13 @@ export class IccoContributor {
       .updateKycAuthority()
       .accounts({
         custodian,
+        claim,
         sale,
         coreBridgeVaa,
+        payer: payer.publicKey,
         systemProgram: web3.SystemProgram.programId,
       })
+      .signers([payer])
       .rpc();
   }
 
@@ -396,9 +490,13 @@ export class IccoContributor {
       })
     );
 
-    // next buyers
+    // next buyers (wrapped SOL is paid out through a temporary account instead)
     const buyerTokenAccounts = await Promise.all(
-      mints.map(async (mint) => getAssociatedTokenAddress(mint, payer.publicKey))
+      mints.map(async (mint) =>
+        mint.equals(NATIVE_MINT)
+          ? this.deriveUnwrapAccount(payer.publicKey)
+          : getAssociatedTokenAddress(mint, payer.publicKey)
+      )
     );
     remainingAccounts.push(
       ...buyerTokenAccounts.map((acct) => {
@@ -412,6 +510,7 @@ export class IccoContributor {
         custodian,
         sale,
         buyer,
+        nativeMint: NATIVE_MINT,
         owner: payer.publicKey,
         systemProgram: web3.SystemProgram.programId,
       })
@@ -475,9 +574,13 @@ export class IccoContributor {
       })
     );
 
-    // next buyers
+    // next buyers (wrapped SOL is paid out through a temporary account instead)
     const buyerTokenAccounts = await Promise.all(
-      mints.map(async (mint) => getAssociatedTokenAddress(mint, payer.publicKey))
+      mints.map(async (mint) =>
+        mint.equals(NATIVE_MINT)
+          ? this.deriveUnwrapAccount(payer.publicKey)
+          : getAssociatedTokenAddress(mint, payer.publicKey)
+      )
     );
     remainingAccounts.push(
       ...buyerTokenAccounts.map((acct) => {
@@ -491,6 +594,7 @@ export class IccoContributor {
         custodian,
         sale,
         buyer,
+        nativeMint: NATIVE_MINT,
         owner: payer.publicKey,
         systemProgram: web3.SystemProgram.programId,
       })
@@ -499,6 +603,43 @@ export class IccoContributor {
       .rpc();
   }
 
+  async submitGovernance(payer: web3.Keypair, governanceVaa: Buffer): Promise<string> {
+    const program = this.program;
+    const custodian = this.custodian;
+
+    // first post signed vaa to wormhole
+    await this.postVaa(payer, governanceVaa);
+    const coreBridgeVaa = this.deriveSignedVaaAccount(governanceVaa);
+    const claim = this.deriveClaimAccount(governanceVaa);
+
+    // an upgrade needs everything the bpf upgradeable loader uses
+    const payload = getVaaBody(governanceVaa);
+    const remainingAccounts: web3.AccountMeta[] = [];
+    if (payload.at(INDEX_GOVERNANCE_ACTION) == GOVERNANCE_ACTION_UPGRADE_CONTRACT) {
+      const buffer = new web3.PublicKey(payload.subarray(INDEX_GOVERNANCE_PAYLOAD, INDEX_GOVERNANCE_PAYLOAD + 32));
+      remainingAccounts.push(makeWritableAccountMeta(this.deriveProgramDataAccount()));
+      remainingAccounts.push(makeWritableAccountMeta(program.programId));
+      remainingAccounts.push(makeWritableAccountMeta(buffer));
+      remainingAccounts.push(makeReadOnlyAccountMeta(this.deriveUpgradeAuthorityAccount()));
+      remainingAccounts.push(makeReadOnlyAccountMeta(web3.SYSVAR_RENT_PUBKEY));
+      remainingAccounts.push(makeReadOnlyAccountMeta(web3.SYSVAR_CLOCK_PUBKEY));
+      remainingAccounts.push(makeReadOnlyAccountMeta(BPF_LOADER_UPGRADEABLE_PROGRAM_ID));
+    }
+
+    return program.methods
+      .submitGovernance()
+      .accounts({
+        custodian,
+        claim,
+        coreBridgeVaa,
+        payer: payer.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([payer])
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+  }
+
   async getCustodian() {
     return this.program.account.custodian.fetch(this.custodian);
   }
@@ -511,6 +652,27 @@ export class IccoContributor {
     return this.program.account.buyer.fetch(this.deriveBuyerAccount(saleId, buyer));
   }
 
+  async getEvents(tx: string): Promise<Event[]> {
+    const connection = this.program.provider.connection;
+
+    // the transaction may not have been confirmed yet
+    let response: web3.TransactionResponse = null;
+    for (let i = 0; response == null && i < 10; ++i) {
+      response = await connection.getTransaction(tx, { commitment: "confirmed" });
+      if (response == null) {
+        await new Promise((r) => setTimeout(r, 500));
+      }
+    }
+    if (response == null) {
+      throw new Error(`transaction not found: ${tx}`);
+    }
+
+    const events: Event[] = [];
+    const parser = new EventParser(this.program.programId, this.program.coder);
+    parser.parseLogs(response.meta.logMessages, (event) => events.push(event));
+    return events;
+  }
+
   async postVaa(payer: web3.Keypair, signedVaa: Buffer): Promise<void> {
     //return postVaa(this.program.provider.connection, payer, this.wormhole, signedVaa);
     await this.postVaaWithRetry(
@@ -526,6 +688,22 @@ export class IccoContributor {
     );
   }
 
+  deriveProgramDataAccount(): web3.PublicKey {
+    return deriveAddress([this.program.programId.toBytes()], BPF_LOADER_UPGRADEABLE_PROGRAM_ID);
+  }
+
+  deriveUpgradeAuthorityAccount(): web3.PublicKey {
+    return deriveAddress([Buffer.from("icco-upgrade")], this.program.programId);
+  }
+
+  deriveClaimAccount(signedVaa: Buffer): web3.PublicKey {
+    const body = signedVaa.subarray(6 + 66 * signedVaa[5]);
+    const emitterChain = body.subarray(8, 10);
+    const emitterAddress = body.subarray(10, 42);
+    const sequence = body.subarray(42, 50);
+    return deriveAddress([Buffer.from("icco-claim"), emitterAddress, emitterChain, sequence], this.program.programId);
+  }
+
   deriveSealedTransferMessageAccount(saleId: Buffer, mint: web3.PublicKey): web3.PublicKey {
     return deriveAddress([Buffer.from("bridge-sealed"), saleId, mint.toBytes()], this.program.programId);
   }
@@ -542,6 +720,10 @@ export class IccoContributor {
     return deriveAddress([Buffer.from("icco-buyer"), saleId, buyer.toBuffer()], this.program.programId);
   }
 
+  deriveUnwrapAccount(owner: web3.PublicKey): web3.PublicKey {
+    return deriveAddress([Buffer.from("icco-unwrap"), owner.toBuffer()], this.program.programId);
+  }
+
   deriveSignedVaaAccount(signedVaa: Buffer): web3.PublicKey {
     const hash = hashVaaPayload(signedVaa);
     return deriveAddress([Buffer.from("PostedVAA"), hash], this.wormhole);
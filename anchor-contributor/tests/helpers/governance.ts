@@ -0,0 +1,30 @@
+import { web3 } from "@project-serum/anchor";
+
+// same module name as the terra contributor, left-zero-padded to 32 bytes
+const GOVERNANCE_MODULE = "TokenSaleContributor";
+
+const GOVERNANCE_ACTION_UPGRADE_CONTRACT = 1;
+const GOVERNANCE_ACTION_UPDATE_CONDUCTOR = 2;
+
+export function encodeUpgradeContract(targetChain: number, buffer: web3.PublicKey): Buffer {
+  const encoded = encodeGovernanceHeader(GOVERNANCE_ACTION_UPGRADE_CONTRACT, targetChain, 32);
+  encoded.write(buffer.toBuffer().toString("hex"), 35, "hex");
+  return encoded;
+}
+
+export function encodeUpdateConductor(targetChain: number, conductorChain: number, conductorAddress: string): Buffer {
+  const encoded = encodeGovernanceHeader(GOVERNANCE_ACTION_UPDATE_CONDUCTOR, targetChain, 34);
+  encoded.writeUint16BE(conductorChain, 35);
+  encoded.write(conductorAddress, 37, "hex");
+  return encoded;
+}
+
+function encodeGovernanceHeader(action: number, targetChain: number, payloadLength: number): Buffer {
+  const encoded = Buffer.alloc(35 + payloadLength);
+
+  const module = Buffer.from(GOVERNANCE_MODULE);
+  encoded.write(module.toString("hex"), 32 - module.length, "hex");
+  encoded.writeUint8(action, 32);
+  encoded.writeUint16BE(targetChain, 33);
+  return encoded;
+}
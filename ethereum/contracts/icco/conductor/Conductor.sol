@@ -207,8 +207,9 @@ contract Conductor is ConductorGovernance, ConductorEvents, ReentrancyGuard {
                     tokenIndex: uint8(i),
                     tokenAddress: acceptedTokens[i].tokenAddress
                 });
-                /// only allow 8 accepted tokens for the Solana Contributor
-                require(_state.solanaAcceptedTokens.length < 8, "22");
+                /// only allow 11 accepted tokens for the Solana Contributor, which is as many as
+                /// fit in one of its claim transactions
+                require(_state.solanaAcceptedTokens.length < 11, "22");
                 /// save in contract storage
                 _state.solanaAcceptedTokens.push(solanaToken);
             }
@@ -3867,12 +3867,12 @@ contract("ICCO", function(accounts) {
       KYC_AUTHORITY,
     ];
 
-    // make sure createSale fails when trying to pass more than 8 tokens
+    // make sure createSale fails when trying to pass more than 11 tokens
     {
       let testAcceptedTokens = [];
 
-      // add 9 tokens to the accepted tokens list
-      for (let i = 0; i < 9; i++) {
+      // add 12 tokens to the accepted tokens list
+      for (let i = 0; i < 12; i++) {
         // create random ethereum address for the accepted token (w/ wallet generation)
         const wallet = ethers.Wallet.createRandom();
         let defaultToken = [
@@ -3885,7 +3885,7 @@ contract("ICCO", function(accounts) {
 
       let failed = false;
       try {
-        // try to create sale with too many solana tokens (greater than 8)
+        // try to create sale with too many solana tokens (greater than 11)
         await initialized.methods.createSale(saleParams, testAcceptedTokens).send({
           value: WORMHOLE_FEE * 2,
           from: SELLER,
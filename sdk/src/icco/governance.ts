@@ -0,0 +1,39 @@
+import { ethers } from "ethers";
+import { ChainId } from "@certusone/wormhole-sdk";
+
+// governance VAAs for the Terra and Solana contributors carry this module name
+const CONTRIBUTOR_GOVERNANCE_MODULE = "TokenSaleContributor";
+
+const GOVERNANCE_ACTION_UPGRADE_CONTRACT = 1;
+const GOVERNANCE_ACTION_UPDATE_CONDUCTOR = 2;
+
+// newContract is the new code ID on Terra or the program buffer on Solana
+export function encodeContributorUpgradeContract(targetChain: ChainId, newContract: ethers.BytesLike): Buffer {
+  return encodeContributorGovernance(
+    GOVERNANCE_ACTION_UPGRADE_CONTRACT,
+    targetChain,
+    ethers.utils.arrayify(ethers.utils.hexZeroPad(newContract, 32))
+  );
+}
+
+// a targetChain of 0 updates the conductor on every contributor
+export function encodeContributorUpdateConductor(
+  targetChain: ChainId | 0,
+  conductorChain: ChainId,
+  conductorAddress: ethers.BytesLike
+): Buffer {
+  const payload = Buffer.alloc(34);
+  payload.writeUInt16BE(conductorChain, 0);
+  payload.set(ethers.utils.arrayify(ethers.utils.hexZeroPad(conductorAddress, 32)), 2);
+  return encodeContributorGovernance(GOVERNANCE_ACTION_UPDATE_CONDUCTOR, targetChain, payload);
+}
+
+function encodeContributorGovernance(action: number, targetChain: number, payload: Uint8Array): Buffer {
+  const header = Buffer.alloc(35);
+
+  const module = Buffer.from(CONTRIBUTOR_GOVERNANCE_MODULE);
+  header.set(module, 32 - module.length);
+  header.writeUInt8(action, 32);
+  header.writeUInt16BE(targetChain, 33);
+  return Buffer.concat([header, payload]);
+}
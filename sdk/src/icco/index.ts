@@ -7,6 +7,7 @@ export * from "./collectContribution";
 export * from "./contribute";
 export * from "./createSale";
 export * from "./getters";
+export * from "./governance";
 export * from "./initSale";
 export * from "./misc";
 export * from "./registerChain";
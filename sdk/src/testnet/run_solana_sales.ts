@@ -669,7 +669,8 @@ describe("Testnet ICCO Sales", () => {
                 tokenIndex,
                 amount,
                 buyer.publicKey
-              )
+              ),
+              buyer.publicKey
             );
             console.log(
               `buyer ${i}, tokenIndex: ${tokenIndex}, contribute: ${solanaTx}`
@@ -1137,7 +1138,8 @@ describe("Testnet ICCO Sales", () => {
                 tokenIndex,
                 amount,
                 buyer.publicKey
-              )
+              ),
+              buyer.publicKey
             );
             console.log(
               `buyer ${i}, tokenIndex: ${tokenIndex}, contribute: ${solanaTx}`
@@ -1,16 +1,19 @@
 use cosmwasm_std::{
-    entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
+    entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response,
 };
 
 use crate::{
+    error::ContractError,
     execute::{
-        attest_contributions, claim_allocation, claim_refund, contribute, init_sale, sale_aborted,
-        sale_sealed,
+        attest_contributions, claim_allocation, claim_refund, contribute, contribution_received,
+        init_sale, kyc_authority_updated, sale_aborted, sale_sealed, submit_vaa,
+        CONTRIBUTE_REPLY_ID,
     },
     msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg},
     query::{
-        query_accepted_token, query_config, query_sale_registry, query_sale_status,
-        query_sale_times, query_total_allocation, query_total_contribution,
+        query_accepted_token, query_accepted_tokens, query_all_sales, query_buyer_contributions,
+        query_buyer_status, query_config, query_sale_registry, query_sale_status, query_sale_times,
+        query_total_allocation, query_total_contribution,
     },
     state::{Config, CONFIG},
 };
@@ -19,7 +22,7 @@ use crate::{
 pub const CHAIN_ID: u16 = 3;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     Ok(Response::new())
 }
 
@@ -29,8 +32,10 @@ pub fn instantiate(
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let cfg = Config {
+        gov_chain: msg.gov_chain,
+        gov_address: msg.gov_address.as_slice().to_vec(),
         wormhole_contract: msg.wormhole_contract,
         token_bridge_contract: msg.token_bridge_contract,
         conductor_chain: msg.conductor_chain,
@@ -41,23 +46,40 @@ pub fn instantiate(
     Ok(Response::default())
 }
 
-// When CW20 transfers complete, we need to verify the actual amount that is being transferred out
-// of the bridge. This is to handle fee tokens where the amount expected to be transferred may be
-// less due to burns, fees, etc.
+// When CW20 transfers complete, we need to verify the actual amount that was transferred in.
+// This is to handle fee tokens where the amount received may be less than expected due to
+// burns, fees, etc.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(_deps: DepsMut, _env: Env, _msg: Reply) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        CONTRIBUTE_REPLY_ID => contribution_received(deps, env),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::InitSale { data } => init_sale(deps, env, info, &data),
         ExecuteMsg::Contribute {
             sale_id,
             token_index,
             amount,
-        } => contribute(deps, env, info, sale_id.as_slice(), token_index, amount),
+            signature,
+        } => contribute(
+            deps,
+            env,
+            info,
+            sale_id.as_slice(),
+            token_index,
+            amount,
+            &signature,
+        ),
         ExecuteMsg::AttestContributions { sale_id } => {
             attest_contributions(deps, env, info, &sale_id)
         }
@@ -71,12 +93,14 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             sale_id,
             token_index,
         } => claim_refund(deps, env, info, &sale_id, token_index),
+        ExecuteMsg::KycAuthorityUpdated { data } => kyc_authority_updated(deps, env, info, &data),
+        ExecuteMsg::SubmitVaa { data } => submit_vaa(deps, env, info, &data),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    let response = match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::SaleRegistry { sale_id } => to_binary(&query_sale_registry(deps, &sale_id)?),
         QueryMsg::SaleStatus { sale_id } => to_binary(&query_sale_status(deps, &sale_id)?),
@@ -93,5 +117,18 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             sale_id,
             token_index,
         } => to_binary(&query_total_allocation(deps, &sale_id, token_index)?),
-    }
+        QueryMsg::BuyerStatus {
+            sale_id,
+            token_index,
+            buyer,
+        } => to_binary(&query_buyer_status(deps, &sale_id, token_index, buyer)?),
+        QueryMsg::AllSales { start_after, limit } => {
+            to_binary(&query_all_sales(deps, start_after, limit)?)
+        }
+        QueryMsg::AcceptedTokens { sale_id } => to_binary(&query_accepted_tokens(deps, &sale_id)?),
+        QueryMsg::BuyerContributions { sale_id, buyer } => {
+            to_binary(&query_buyer_contributions(deps, &sale_id, buyer)?)
+        }
+    }?;
+    Ok(response)
 }
@@ -1,62 +1,111 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{OverflowError, StdError};
 use thiserror::Error;
 
-#[derive(Error, Debug)]
-pub enum ContributorError {
-    #[error("InvalidVAAAction")]
-    InvalidVAAAction,
+// sale ids and addresses are hex encoded so they read the same way as the
+// attributes emitted by each handler
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("InvalidVAAAction: {action}")]
+    InvalidVAAAction { action: u8 },
+
+    #[error("VaaAlreadyExecuted")]
+    VaaAlreadyExecuted,
+
+    #[error("InvalidEmitter: expected {expected_chain}:{expected_address}, got {actual_chain}:{actual_address}")]
+    InvalidEmitter {
+        expected_chain: u16,
+        expected_address: String,
+        actual_chain: u16,
+        actual_address: String,
+    },
+
+    #[error("InvalidGovernanceModule")]
+    InvalidGovernanceModule,
+
+    #[error("UnknownReplyId: {id}")]
+    UnknownReplyId { id: u64 },
 
     #[error("NotInitialized")]
     NotInitialized,
 
-    #[error("SaleNotFound")]
-    SaleNotFound,
+    #[error("SaleNotFound: {sale_id}")]
+    SaleNotFound { sale_id: String },
 
-    #[error("SaleAlreadyExists")]
-    SaleAlreadyExists,
+    #[error("SaleAlreadyExists: {sale_id}")]
+    SaleAlreadyExists { sale_id: String },
 
-    #[error("SaleStatusNotFound")]
-    SaleStatusNotFound,
+    #[error("SaleStatusNotFound: {sale_id}")]
+    SaleStatusNotFound { sale_id: String },
 
-    #[error("SaleTimesNotFound")]
-    SaleTimesNotFound,
+    #[error("SaleTimesNotFound: {sale_id}")]
+    SaleTimesNotFound { sale_id: String },
 
-    #[error("AcceptedTokenNotFound")]
-    AcceptedTokenNotFound,
+    #[error("AcceptedTokenNotFound: {sale_id}, token {token_index}")]
+    AcceptedTokenNotFound { sale_id: String, token_index: u8 },
 
-    #[error("ContributionNotFound")]
-    ContributionNotFound,
+    #[error("ContributionNotFound: {sale_id}, token {token_index}")]
+    ContributionNotFound { sale_id: String, token_index: u8 },
 
-    #[error("AllocationNotFound")]
-    AllocationNotFound,
+    #[error("AllocationNotFound: {sale_id}, token {token_index}")]
+    AllocationNotFound { sale_id: String, token_index: u8 },
 
-    #[error("SaleNotFinished")]
-    SaleNotFinished,
+    #[error("BuyerStatusNotFound: {sale_id}, token {token_index}")]
+    BuyerStatusNotFound { sale_id: String, token_index: u8 },
 
-    #[error("SaleAlreadySealedOrAborted")]
-    SaleAlreadySealedOrAborted,
+    #[error("SaleNotFinished: {sale_id}")]
+    SaleNotFinished { sale_id: String },
 
-    #[error("SaleAborted")]
-    SaleAborted,
+    #[error("SaleAlreadySealedOrAborted: {sale_id}")]
+    SaleAlreadySealedOrAborted { sale_id: String },
 
-    #[error("SaleNotStarted")]
-    SaleNotStarted,
+    #[error("SaleAborted: {sale_id}")]
+    SaleAborted { sale_id: String },
 
-    #[error("SaleEnded")]
-    SaleEnded,
+    #[error("SaleNotSealed: {sale_id}")]
+    SaleNotSealed { sale_id: String },
 
-    #[error("WrongChain")]
-    WrongChain,
-}
+    #[error("SaleNotAborted: {sale_id}")]
+    SaleNotAborted { sale_id: String },
+
+    #[error("SaleNotStarted: {sale_id}")]
+    SaleNotStarted { sale_id: String },
+
+    #[error("SaleEnded: {sale_id}")]
+    SaleEnded { sale_id: String },
+
+    #[error("WrongChain: expected {expected}, got {actual}")]
+    WrongChain { expected: u16, actual: u16 },
+
+    #[error("ZeroAmount")]
+    ZeroAmount,
+
+    #[error("AmountTooLarge")]
+    AmountTooLarge,
+
+    #[error("IncorrectFunds")]
+    IncorrectFunds,
+
+    #[error("InsufficientFunds: {sale_id}")]
+    InsufficientFunds { sale_id: String },
+
+    #[error("InvalidAcceptedToken: {sale_id}, token {token_index}")]
+    InvalidAcceptedToken { sale_id: String, token_index: u8 },
+
+    #[error("InvalidKycSignature")]
+    InvalidKycSignature,
+
+    #[error("AllocationsLocked: {sale_id}")]
+    AllocationsLocked { sale_id: String },
 
-impl ContributorError {
-    pub fn std(&self) -> StdError {
-        StdError::GenericErr {
-            msg: format!("{}", self),
-        }
-    }
+    #[error("AllocationAlreadyClaimed: {sale_id}, token {token_index}")]
+    AllocationAlreadyClaimed { sale_id: String, token_index: u8 },
 
-    pub fn std_err<T>(&self) -> Result<T, StdError> {
-        Err(self.std())
-    }
+    #[error("RefundAlreadyClaimed: {sale_id}, token {token_index}")]
+    RefundAlreadyClaimed { sale_id: String, token_index: u8 },
 }
@@ -1,51 +1,88 @@
 use cosmwasm_std::{
-    to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, QueryRequest, Response,
-    StdError, StdResult, Storage, Uint256, WasmMsg, WasmQuery,
+    to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order,
+    QueryRequest, Response, StdError, StdResult, Storage, SubMsg, Uint128, Uint256, WasmMsg,
+    WasmQuery,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, TokenInfoResponse};
+use sha3::{Digest, Keccak256};
+use std::convert::TryFrom;
+use terraswap::asset::{Asset, AssetInfo};
+
+use token_bridge_terra::msg::{
+    ExecuteMsg as TokenBridgeExecuteMsg, QueryMsg as TokenBridgeQueryMsg, WrappedRegistryResponse,
 };
 
 use wormhole::{
+    byte_utils::ByteUtils,
     msg::{ExecuteMsg as WormholeExecuteMsg, QueryMsg as WormholeQueryMsg},
-    state::ParsedVAA,
+    state::{GovernancePacket, ParsedVAA},
 };
 
 use crate::{
-    error::ContributorError,
-    shared::{AcceptedToken, ContributionsSealed, SaleAborted, SaleInit, SaleSealed, SaleStatus},
+    error::ContractError,
+    msg::MigrateMsg,
+    shared::{
+        AcceptedToken, AssetStatus, AuthorityUpdated, ContributionsSealed, SaleAborted, SaleCore,
+        SaleInit, SaleSealed, SaleStatus,
+    },
     state::{
-        SaleMessage, TokenIndexKey, ACCEPTED_TOKENS, CHAIN_ID, CONFIG, SALES, SALE_STATUSES,
-        SALE_TIMES, TOTAL_ALLOCATIONS, TOTAL_CONTRIBUTIONS, ZERO_AMOUNT,
+        claim_buyer_allocation, claim_buyer_refund, load_accepted_token, load_buyer_contribution,
+        load_total_allocation, load_total_contribution, load_total_excess_contribution,
+        update_buyer_contribution, Config, PendingContribution, SaleMessage, TokenIndexKey,
+        UpdateConductor, UpgradeContract, ACCEPTED_TOKENS, ASSET_STATUSES, CHAIN_ID, CONFIG,
+        CONSUMED_VAAS, PENDING_CONTRIBUTION, SALES, SALE_STATUSES, SALE_TIMES, TOTAL_ALLOCATIONS,
+        TOTAL_CONTRIBUTIONS, TOTAL_EXCESS_CONTRIBUTIONS, ZERO_AMOUNT,
     },
 };
 
 // nonce means nothing?
 const WORMHOLE_NONCE: u32 = 0;
 
-pub fn init_sale(deps: DepsMut, env: Env, _info: MessageInfo, vaa: &Binary) -> StdResult<Response> {
+// reply to the cw20 transfer that pulls in a contribution
+pub const CONTRIBUTE_REPLY_ID: u64 = 1;
+
+// governance VAAs addressed to this contract carry this module name
+const GOVERNANCE_MODULE: &str = "TokenSaleContributor";
+
+// luna and the terra stablecoins, the only native denoms a sale can accept
+const TERRA_NATIVE_DENOMS: [&str; 23] = [
+    "uluna", "uaud", "ucad", "uchf", "ucny", "udkk", "ueur", "ugbp", "uhkd", "uidr", "uinr",
+    "ujpy", "ukrw", "umnt", "umyr", "unok", "uphp", "usdr", "usek", "usgd", "uthb", "utwd", "uusd",
+];
+
+pub fn init_sale(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    vaa: &Binary,
+) -> Result<Response, ContractError> {
     let parsed = parse_vaa(deps.as_ref(), env.block.time.seconds(), vaa)?;
     verify_conductor(
         deps.storage,
         parsed.emitter_chain,
         parsed.emitter_address.as_slice(),
     )?;
+    consume_vaa(deps.storage, parsed.hash.as_slice())?;
 
     let message = SaleMessage::deserialize(parsed.payload.as_slice())?;
     if message.id != SaleInit::PAYLOAD_ID {
-        return ContributorError::InvalidVAAAction.std_err();
+        return Err(ContractError::InvalidVAAAction { action: message.id });
     }
 
     let sale_init = SaleInit::deserialize(message.payload)?;
     let sale_id = sale_init.core.id.as_slice();
 
-    let storage = deps.storage;
-    if SALES.may_load(storage, sale_id)? != None {
-        return ContributorError::SaleAlreadyExists.std_err();
+    if SALES.has(deps.storage, sale_id) {
+        return Err(ContractError::SaleAlreadyExists {
+            sale_id: hex::encode(sale_id),
+        });
     }
 
-    SALES.save(storage, sale_id, &sale_init.core)?;
+    SALES.save(deps.storage, sale_id, &sale_init.core)?;
 
     // fresh status
     SALE_STATUSES.save(
-        storage,
+        deps.storage,
         sale_id,
         &SaleStatus {
             is_sealed: false,
@@ -54,19 +91,28 @@ pub fn init_sale(deps: DepsMut, env: Env, _info: MessageInfo, vaa: &Binary) -> S
     )?;
 
     // and times
-    SALE_TIMES.save(storage, sale_id, &sale_init.core.times)?;
+    SALE_TIMES.save(deps.storage, sale_id, &sale_init.core.times)?;
 
     // now save some initial states for contributions and allocations
     for (i, token) in sale_init.accepted_tokens.iter().enumerate() {
         let token_index = i as u8;
 
+        // a bad local token should not take the whole sale down with it. it
+        // is marked invalid instead so nobody can contribute it
+        let asset_status = if token.chain == CHAIN_ID
+            && !is_valid_terra_asset(deps.as_ref(), token.address.as_slice())
+        {
+            AssetStatus::Invalid
+        } else {
+            AssetStatus::Active
+        };
+
         let key: TokenIndexKey = (sale_id, token_index.into());
-        if token.chain == CHAIN_ID {
-            // TODO: check if token is actual CW20
-        }
-        ACCEPTED_TOKENS.save(storage, key.clone(), &token)?;
-        TOTAL_CONTRIBUTIONS.save(storage, key.clone(), &ZERO_AMOUNT)?;
-        TOTAL_ALLOCATIONS.save(storage, key.clone(), &ZERO_AMOUNT)?;
+        ACCEPTED_TOKENS.save(deps.storage, key.clone(), &token)?;
+        TOTAL_CONTRIBUTIONS.save(deps.storage, key.clone(), &ZERO_AMOUNT)?;
+        TOTAL_ALLOCATIONS.save(deps.storage, key.clone(), &ZERO_AMOUNT)?;
+        TOTAL_EXCESS_CONTRIBUTIONS.save(deps.storage, key.clone(), &ZERO_AMOUNT)?;
+        ASSET_STATUSES.save(deps.storage, key.clone(), &asset_status)?;
     }
 
     let sale = &sale_init.core;
@@ -78,39 +124,202 @@ pub fn init_sale(deps: DepsMut, env: Env, _info: MessageInfo, vaa: &Binary) -> S
 }
 
 pub fn contribute(
-    mut deps: DepsMut,
+    deps: DepsMut,
     env: Env,
     info: MessageInfo,
     sale_id: &[u8],
     token_index: u8,
     amount: Uint256,
-) -> StdResult<Response> {
-    // TODO : add signature argument
-    let storage = deps.storage;
-    let status = SALE_STATUSES.load(storage, sale_id)?;
+    signature: &Binary,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount);
+    }
+
+    let sale = match SALES.may_load(deps.storage, sale_id)? {
+        Some(sale) => sale,
+        None => {
+            return Err(ContractError::SaleNotFound {
+                sale_id: hex::encode(sale_id),
+            })
+        }
+    };
+
+    let status = SALE_STATUSES.load(deps.storage, sale_id).map_err(|_| {
+        ContractError::SaleStatusNotFound {
+            sale_id: hex::encode(sale_id),
+        }
+    })?;
     if status.is_aborted {
-        return ContributorError::SaleAborted.std_err();
+        return Err(ContractError::SaleAborted {
+            sale_id: hex::encode(sale_id),
+        });
+    } else if status.is_sealed {
+        return Err(ContractError::SaleAlreadySealedOrAborted {
+            sale_id: hex::encode(sale_id),
+        });
     }
 
-    let times = SALE_TIMES.load(storage, sale_id)?;
+    let times =
+        SALE_TIMES
+            .load(deps.storage, sale_id)
+            .map_err(|_| ContractError::SaleTimesNotFound {
+                sale_id: hex::encode(sale_id),
+            })?;
     let now = env.block.time.seconds();
     if now < times.start {
-        return ContributorError::SaleNotStarted.std_err();
+        return Err(ContractError::SaleNotStarted {
+            sale_id: hex::encode(sale_id),
+        });
     } else if now > times.end {
-        return ContributorError::SaleEnded.std_err();
+        return Err(ContractError::SaleEnded {
+            sale_id: hex::encode(sale_id),
+        });
     }
 
-    let token = ACCEPTED_TOKENS.load(storage, (sale_id, token_index.into()))?;
+    let token = match ACCEPTED_TOKENS.may_load(deps.storage, (sale_id, token_index.into()))? {
+        Some(token) => token,
+        None => {
+            return Err(ContractError::AcceptedTokenNotFound {
+                sale_id: hex::encode(sale_id),
+                token_index,
+            })
+        }
+    };
     if token.chain != CHAIN_ID {
-        return ContributorError::WrongChain.std_err();
+        return Err(ContractError::WrongChain {
+            expected: CHAIN_ID,
+            actual: token.chain,
+        });
+    }
+    if ASSET_STATUSES.load(deps.storage, (sale_id, token_index.into()))? == AssetStatus::Invalid {
+        return Err(ContractError::InvalidAcceptedToken {
+            sale_id: hex::encode(sale_id),
+            token_index,
+        });
     }
 
-    //assert!(USER_ACTION.load(deps.storage).is_err());
+    // the kyc authority signs off on the buyer's running total, so we need
+    // to check the signature against what they have contributed so far
+    let prior_contribution =
+        load_buyer_contribution(deps.storage, sale_id, token_index, &info.sender)?;
+    verify_kyc_signature(
+        deps.as_ref(),
+        &sale,
+        token_index,
+        amount,
+        &info.sender,
+        prior_contribution,
+        signature.as_slice(),
+    )?;
 
-    Ok(Response::new()
+    let asset_info = accepted_asset_info(deps.as_ref(), sale_id, token_index, &token)?;
+    let transfer_amount = to_uint128(amount)?;
+
+    let mut response = Response::new()
         .add_attribute("action", "contribute")
-        .add_attribute("sale_id", Binary::from(sale_id).to_base64())
-        .add_attribute("token_address", Binary::from(token.address).to_base64()))
+        .add_attribute("sale_id", hex::encode(sale_id))
+        .add_attribute("token_index", token_index.to_string())
+        .add_attribute("buyer", info.sender.to_string())
+        .add_attribute("amount", amount.to_string());
+    match &asset_info {
+        AssetInfo::NativeToken { denom } => {
+            // native contributions need to be sent along with this message
+            let sent = info
+                .funds
+                .iter()
+                .find(|coin| coin.denom == *denom)
+                .map(|coin| coin.amount)
+                .unwrap_or_default();
+            if sent != transfer_amount || info.funds.len() != 1 {
+                return Err(ContractError::IncorrectFunds);
+            }
+
+            let buyer_status = update_buyer_contribution(
+                deps.storage,
+                sale_id,
+                token_index,
+                &info.sender,
+                amount,
+            )?;
+            response = response.add_attribute(
+                "total_buyer_contribution",
+                buyer_status.contribution.to_string(),
+            );
+        }
+        AssetInfo::Token { contract_addr } => {
+            // cw20 contributions are pulled from the buyer, who needs to
+            // have approved this contract as a spender beforehand. fee and
+            // burn tokens may deliver less than the requested amount, so
+            // the buyer is only credited in the reply with what arrived,
+            // and never with more than the kyc authority signed off on
+            let balance_before =
+                query_asset_balance(deps.as_ref(), &asset_info, &env.contract.address)?;
+            PENDING_CONTRIBUTION.save(
+                deps.storage,
+                &PendingContribution {
+                    sale_id: sale_id.to_vec(),
+                    token_index,
+                    buyer: info.sender.clone(),
+                    token: contract_addr.clone(),
+                    amount: transfer_amount,
+                    balance_before,
+                },
+            )?;
+
+            response = response.add_submessage(SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.clone(),
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: info.sender.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: transfer_amount,
+                    })?,
+                    funds: vec![],
+                }),
+                CONTRIBUTE_REPLY_ID,
+            ));
+        }
+    }
+
+    Ok(response)
+}
+
+pub fn contribution_received(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let pending = PENDING_CONTRIBUTION.load(deps.storage)?;
+    PENDING_CONTRIBUTION.remove(deps.storage);
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: pending.token,
+    };
+    let balance_after = query_asset_balance(deps.as_ref(), &asset_info, &env.contract.address)?;
+    let received = balance_after.checked_sub(pending.balance_before)?;
+    if received.is_zero() {
+        return Err(ContractError::ZeroAmount);
+    }
+
+    // the signature only covers the requested amount, so anything on top of
+    // it (e.g. a rebasing token) is not credited to the buyer
+    let credited = received.min(pending.amount);
+    let buyer_status = update_buyer_contribution(
+        deps.storage,
+        pending.sale_id.as_slice(),
+        pending.token_index,
+        &pending.buyer,
+        Uint256::from(credited.u128()),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "contribution_received")
+        .add_attribute("sale_id", hex::encode(&pending.sale_id))
+        .add_attribute("token_index", pending.token_index.to_string())
+        .add_attribute("buyer", pending.buyer.to_string())
+        .add_attribute("received", received.to_string())
+        .add_attribute("credited", credited.to_string())
+        .add_attribute(
+            "total_buyer_contribution",
+            buyer_status.contribution.to_string(),
+        ))
 }
 
 pub fn attest_contributions(
@@ -118,16 +327,29 @@ pub fn attest_contributions(
     env: Env,
     info: MessageInfo,
     sale_id: &[u8],
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let storage = deps.storage;
-    let status = SALE_STATUSES.load(storage, sale_id)?;
+    let status = SALE_STATUSES
+        .load(storage, sale_id)
+        .map_err(|_| ContractError::SaleNotFound {
+            sale_id: hex::encode(sale_id),
+        })?;
     if status.is_sealed || status.is_aborted {
-        return ContributorError::SaleAlreadySealedOrAborted.std_err();
+        return Err(ContractError::SaleAlreadySealedOrAborted {
+            sale_id: hex::encode(sale_id),
+        });
     }
 
-    let times = SALE_TIMES.load(storage, sale_id)?;
+    let times =
+        SALE_TIMES
+            .load(storage, sale_id)
+            .map_err(|_| ContractError::SaleTimesNotFound {
+                sale_id: hex::encode(sale_id),
+            })?;
     if env.block.time.seconds() <= times.end {
-        return ContributorError::SaleNotFinished.std_err();
+        return Err(ContractError::SaleNotFinished {
+            sale_id: hex::encode(sale_id),
+        });
     }
 
     let accepted_tokens: Vec<AcceptedToken> = ACCEPTED_TOKENS
@@ -169,93 +391,216 @@ pub fn sale_sealed(
     env: Env,
     _info: MessageInfo,
     vaa: &Binary,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let parsed = parse_vaa(deps.as_ref(), env.block.time.seconds(), vaa)?;
     verify_conductor(
         deps.storage,
         parsed.emitter_chain,
         parsed.emitter_address.as_slice(),
     )?;
+    consume_vaa(deps.storage, parsed.hash.as_slice())?;
 
     let message = SaleMessage::deserialize(parsed.payload.as_slice())?;
     if message.id != SaleSealed::PAYLOAD_ID {
-        return ContributorError::InvalidVAAAction.std_err();
+        return Err(ContractError::InvalidVAAAction { action: message.id });
     }
 
     let sale_sealed = SaleSealed::deserialize(message.payload)?;
     let sale_id = sale_sealed.sale_id.as_slice();
 
-    let storage = deps.storage;
-    let status = SALE_STATUSES.load(storage, sale_id)?;
+    let status =
+        SALE_STATUSES
+            .load(deps.storage, sale_id)
+            .map_err(|_| ContractError::SaleNotFound {
+                sale_id: hex::encode(sale_id),
+            })?;
     if status.is_sealed || status.is_aborted {
-        return ContributorError::SaleAlreadySealedOrAborted.std_err();
+        return Err(ContractError::SaleAlreadySealedOrAborted {
+            sale_id: hex::encode(sale_id),
+        });
     }
 
-    let sale = SALES.load(storage, sale_id)?;
+    let sale = SALES
+        .load(deps.storage, sale_id)
+        .map_err(|_| ContractError::SaleNotFound {
+            sale_id: hex::encode(sale_id),
+        })?;
 
-    // sale token handling
-    if sale.token_chain == CHAIN_ID {
-        // lol
-    } else {
-        // get the wrapped address
-        // verify balance is > 0
-        // sum total allocations
-        // verify the balance is >= total allocations
+    // save the allocations and excess contributions the conductor computed
+    // for each accepted token. buyers claim their share of these later
+    for allocation in sale_sealed.allocations.iter() {
+        let key: TokenIndexKey = (sale_id, allocation.token_index.into());
+        if !ACCEPTED_TOKENS.has(deps.storage, key.clone()) {
+            return Err(ContractError::AcceptedTokenNotFound {
+                sale_id: hex::encode(sale_id),
+                token_index: allocation.token_index,
+            });
+        }
+        TOTAL_ALLOCATIONS.save(deps.storage, key.clone(), &allocation.allocated)?;
+        TOTAL_EXCESS_CONTRIBUTIONS.save(deps.storage, key, &allocation.excess_contributed)?;
+    }
+
+    let accepted_tokens: Vec<AcceptedToken> = ACCEPTED_TOKENS
+        .prefix(sale_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| -> AcceptedToken {
+            let (_, token) = item.unwrap();
+            token
+        })
+        .collect();
+
+    // the conductor sends the sale token for Terra buyers before sealing, so
+    // there has to be enough of it here to pay out every local allocation
+    let mut total_allocations = ZERO_AMOUNT;
+    for (token_index, token) in accepted_tokens.iter().enumerate() {
+        if token.chain == CHAIN_ID {
+            let allocation = load_total_allocation(deps.storage, sale_id, token_index as u8)?;
+            total_allocations = total_allocations.checked_add(allocation)?;
+        }
+    }
+
+    let sale_token = sale_token_asset_info(deps.as_ref(), &sale)?;
+    let total_allocations =
+        to_sale_token_amount(deps.as_ref(), &sale, &sale_token, total_allocations)?;
+    let balance = query_asset_balance(deps.as_ref(), &sale_token, &env.contract.address)?;
+    if balance < total_allocations {
+        return Err(ContractError::InsufficientFunds {
+            sale_id: hex::encode(sale_id),
+        });
     }
 
     SALE_STATUSES.update(
-        storage,
+        deps.storage,
         sale_id,
-        |status: Option<SaleStatus>| -> StdResult<SaleStatus> {
+        |status: Option<SaleStatus>| -> Result<SaleStatus, ContractError> {
             match status {
                 Some(_) => Ok(SaleStatus {
                     is_sealed: true,
                     is_aborted: false,
                 }),
-                None => ContributorError::SaleNotFound.std_err(),
+                None => Err(ContractError::SaleNotFound {
+                    sale_id: hex::encode(sale_id),
+                }),
             }
         },
     )?;
 
-    let accepted_tokens: Vec<AcceptedToken> = ACCEPTED_TOKENS
-        .prefix(sale_id)
-        .range(storage, None, None, Order::Ascending)
-        .map(|item| -> AcceptedToken {
-            let (_, token) = item.unwrap();
-            token
-        })
-        .collect();
+    // transfer contributions to conductor. whatever was not allocated stays
+    // here so buyers can claim it back as excess
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+    for (token_index, token) in accepted_tokens.iter().enumerate() {
+        if token.chain != CHAIN_ID {
+            continue;
+        }
 
-    // transfer contributions to conductor
-    let cfg = CONFIG.load(storage)?;
-    if cfg.conductor_chain == CHAIN_ID {
-        // lol
-    } else {
-        for token in accepted_tokens.iter() {
-            if token.chain != CHAIN_ID {
-                continue;
-            }
+        let key: TokenIndexKey = (sale_id, (token_index as u8).into());
+        if ASSET_STATUSES.load(deps.storage, key.clone())? == AssetStatus::Invalid {
+            continue;
+        }
 
-            // do token bridge transfers here
-            let _address = token.address.clone();
+        let contributions = TOTAL_CONTRIBUTIONS.load(deps.storage, key.clone())?;
+        let excess = TOTAL_EXCESS_CONTRIBUTIONS.load(deps.storage, key.clone())?;
+        let amount = to_uint128(contributions.checked_sub(excess)?)?;
+        if amount.is_zero() {
+            ASSET_STATUSES.save(deps.storage, key, &AssetStatus::NothingToTransfer)?;
+            continue;
         }
+
+        let asset_info = accepted_asset_info(deps.as_ref(), sale_id, token_index as u8, token)?;
+        if cfg.conductor_chain == CHAIN_ID {
+            let recipient = deps
+                .api
+                .addr_humanize(&sale.recipient.as_slice().get_address(0))?;
+            messages.push(transfer_asset_msg(&asset_info, &recipient, amount)?);
+        } else {
+            messages.extend(bridge_asset_msgs(
+                &cfg,
+                asset_info,
+                amount,
+                &sale.recipient,
+            )?);
+        }
+        ASSET_STATUSES.save(deps.storage, key, &AssetStatus::TransferredToConductor)?;
     }
 
     Ok(Response::new()
+        .add_messages(messages)
         .add_attribute("action", "sale_sealed")
         .add_attribute("sale_id", hex::encode(sale_id)))
 }
 
 pub fn claim_allocation(
-    mut deps: DepsMut,
+    deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    sale_id: &Binary,
+    sale_id: &[u8],
     token_index: u8,
-) -> StdResult<Response> {
-    // TODO devs do something
+) -> Result<Response, ContractError> {
+    let sale = match SALES.may_load(deps.storage, sale_id)? {
+        Some(sale) => sale,
+        None => {
+            return Err(ContractError::SaleNotFound {
+                sale_id: hex::encode(sale_id),
+            })
+        }
+    };
+
+    let status = SALE_STATUSES.load(deps.storage, sale_id).map_err(|_| {
+        ContractError::SaleStatusNotFound {
+            sale_id: hex::encode(sale_id),
+        }
+    })?;
+    if !status.is_sealed {
+        return Err(ContractError::SaleNotSealed {
+            sale_id: hex::encode(sale_id),
+        });
+    }
+
+    // allocations may be locked up for some time after the sale is sealed
+    if env.block.time.seconds() < sale.times.unlock_allocation {
+        return Err(ContractError::AllocationsLocked {
+            sale_id: hex::encode(sale_id),
+        });
+    }
 
-    Ok(Response::new().add_attribute("action", "claim_allocation"))
+    let buyer_status = claim_buyer_allocation(deps.storage, sale_id, token_index, &info.sender)?;
+
+    // the buyer is owed their share of the allocation and excess contributions
+    // for this token, proportional to how much they contributed
+    let total_contribution = load_total_contribution(deps.storage, sale_id, token_index)?;
+    let (allocation, excess) = match total_contribution.is_zero() {
+        true => (ZERO_AMOUNT, ZERO_AMOUNT),
+        false => (
+            load_total_allocation(deps.storage, sale_id, token_index)?
+                .multiply_ratio(buyer_status.contribution, total_contribution),
+            load_total_excess_contribution(deps.storage, sale_id, token_index)?
+                .multiply_ratio(buyer_status.contribution, total_contribution),
+        ),
+    };
+
+    let mut response = Response::new();
+
+    let sale_token = sale_token_asset_info(deps.as_ref(), &sale)?;
+    let allocation = to_sale_token_amount(deps.as_ref(), &sale, &sale_token, allocation)?;
+    if !allocation.is_zero() {
+        response = response.add_message(transfer_asset_msg(&sale_token, &info.sender, allocation)?);
+    }
+
+    let excess = to_uint128(excess)?;
+    if !excess.is_zero() {
+        let token = load_accepted_token(deps.storage, sale_id, token_index)?;
+        let asset_info = accepted_asset_info(deps.as_ref(), sale_id, token_index, &token)?;
+        response = response.add_message(transfer_asset_msg(&asset_info, &info.sender, excess)?);
+    }
+
+    Ok(response
+        .add_attribute("action", "claim_allocation")
+        .add_attribute("sale_id", hex::encode(sale_id))
+        .add_attribute("token_index", token_index.to_string())
+        .add_attribute("buyer", info.sender.to_string())
+        .add_attribute("allocation", allocation.to_string())
+        .add_attribute("excess", excess.to_string()))
 }
 
 pub fn sale_aborted(
@@ -263,33 +608,53 @@ pub fn sale_aborted(
     env: Env,
     _info: MessageInfo,
     vaa: &Binary,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let parsed = parse_vaa(deps.as_ref(), env.block.time.seconds(), vaa)?;
     verify_conductor(
         deps.storage,
         parsed.emitter_chain,
         parsed.emitter_address.as_slice(),
     )?;
+    consume_vaa(deps.storage, parsed.hash.as_slice())?;
 
     let message = SaleMessage::deserialize(parsed.payload.as_slice())?;
     if message.id != SaleAborted::PAYLOAD_ID {
-        return ContributorError::InvalidVAAAction.std_err();
+        return Err(ContractError::InvalidVAAAction { action: message.id });
     }
 
     let sale_aborted = SaleAborted::deserialize(message.payload)?;
     let sale_id = sale_aborted.sale_id.as_slice();
-    let sale = SALES.load(deps.storage, sale_id)?;
+    let sale = SALES
+        .load(deps.storage, sale_id)
+        .map_err(|_| ContractError::SaleNotFound {
+            sale_id: hex::encode(sale_id),
+        })?;
+
+    // a sealed sale may already have paid out allocations, so it can no longer
+    // be turned into refunds
+    let status = SALE_STATUSES.load(deps.storage, sale_id).map_err(|_| {
+        ContractError::SaleStatusNotFound {
+            sale_id: hex::encode(sale_id),
+        }
+    })?;
+    if status.is_sealed || status.is_aborted {
+        return Err(ContractError::SaleAlreadySealedOrAborted {
+            sale_id: hex::encode(sale_id),
+        });
+    }
 
     SALE_STATUSES.update(
         deps.storage,
         sale_id,
-        |status: Option<SaleStatus>| -> StdResult<SaleStatus> {
+        |status: Option<SaleStatus>| -> Result<SaleStatus, ContractError> {
             match status {
                 Some(_) => Ok(SaleStatus {
                     is_sealed: false,
                     is_aborted: true,
                 }),
-                None => ContributorError::SaleNotFound.std_err(),
+                None => Err(ContractError::SaleNotFound {
+                    sale_id: hex::encode(sale_id),
+                }),
             }
         },
     )?;
@@ -300,20 +665,153 @@ pub fn sale_aborted(
 }
 
 pub fn claim_refund(
-    mut deps: DepsMut,
-    env: Env,
+    deps: DepsMut,
+    _env: Env,
     info: MessageInfo,
-    sale_id: &Binary,
+    sale_id: &[u8],
     token_index: u8,
-) -> StdResult<Response> {
-    // TODO devs do something
+) -> Result<Response, ContractError> {
+    let status = match SALE_STATUSES.may_load(deps.storage, sale_id)? {
+        Some(status) => status,
+        None => {
+            return Err(ContractError::SaleNotFound {
+                sale_id: hex::encode(sale_id),
+            })
+        }
+    };
+    if !status.is_aborted {
+        return Err(ContractError::SaleNotAborted {
+            sale_id: hex::encode(sale_id),
+        });
+    }
+
+    // buyers get back exactly what they contributed
+    let buyer_status = claim_buyer_refund(deps.storage, sale_id, token_index, &info.sender)?;
+    let refund = to_uint128(buyer_status.contribution)?;
 
-    Ok(Response::new().add_attribute("action", "claim_refund"))
+    let mut response = Response::new();
+    if !refund.is_zero() {
+        let token = load_accepted_token(deps.storage, sale_id, token_index)?;
+        let asset_info = accepted_asset_info(deps.as_ref(), sale_id, token_index, &token)?;
+        response = response.add_message(transfer_asset_msg(&asset_info, &info.sender, refund)?);
+    }
+
+    Ok(response
+        .add_attribute("action", "claim_refund")
+        .add_attribute("sale_id", hex::encode(sale_id))
+        .add_attribute("token_index", token_index.to_string())
+        .add_attribute("buyer", info.sender.to_string())
+        .add_attribute("refund", refund.to_string()))
 }
 
-/*
-pub fn handle_upgrade_contract(_deps: DepsMut, env: Env, data: &Vec<u8>) -> StdResult<Response> {
-    let UpgradeContract { new_contract } = UpgradeContract::deserialize(&data)?;
+pub fn kyc_authority_updated(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    vaa: &Binary,
+) -> Result<Response, ContractError> {
+    let parsed = parse_vaa(deps.as_ref(), env.block.time.seconds(), vaa)?;
+    verify_conductor(
+        deps.storage,
+        parsed.emitter_chain,
+        parsed.emitter_address.as_slice(),
+    )?;
+    consume_vaa(deps.storage, parsed.hash.as_slice())?;
+
+    let message = SaleMessage::deserialize(parsed.payload.as_slice())?;
+    if message.id != AuthorityUpdated::PAYLOAD_ID {
+        return Err(ContractError::InvalidVAAAction { action: message.id });
+    }
+
+    let authority_updated = AuthorityUpdated::deserialize(message.payload)?;
+    let sale_id = authority_updated.sale_id.as_slice();
+
+    let mut sale = match SALES.may_load(deps.storage, sale_id)? {
+        Some(sale) => sale,
+        None => {
+            return Err(ContractError::SaleNotFound {
+                sale_id: hex::encode(sale_id),
+            })
+        }
+    };
+
+    // the authority only matters while contributions are still accepted
+    let status = SALE_STATUSES.load(deps.storage, sale_id).map_err(|_| {
+        ContractError::SaleStatusNotFound {
+            sale_id: hex::encode(sale_id),
+        }
+    })?;
+    if status.is_sealed || status.is_aborted {
+        return Err(ContractError::SaleAlreadySealedOrAborted {
+            sale_id: hex::encode(sale_id),
+        });
+    }
+    if env.block.time.seconds() > sale.times.end {
+        return Err(ContractError::SaleEnded {
+            sale_id: hex::encode(sale_id),
+        });
+    }
+
+    sale.authority = authority_updated.new_authority;
+    SALES.save(deps.storage, sale_id, &sale)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "kyc_authority_updated")
+        .add_attribute("sale_id", hex::encode(sale_id))
+        .add_attribute("authority", hex::encode(&sale.authority)))
+}
+
+pub fn submit_vaa(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    vaa: &Binary,
+) -> Result<Response, ContractError> {
+    let parsed = parse_vaa(deps.as_ref(), env.block.time.seconds(), vaa)?;
+    verify_governance(
+        deps.storage,
+        parsed.emitter_chain,
+        parsed.emitter_address.as_slice(),
+    )?;
+
+    consume_vaa(deps.storage, parsed.hash.as_slice())?;
+
+    let packet = GovernancePacket::deserialize(&parsed.payload)?;
+    if packet.module != governance_module() {
+        return Err(ContractError::InvalidGovernanceModule);
+    }
+
+    match packet.action {
+        1u8 => {
+            // an upgrade has to name this contributor's chain explicitly
+            if packet.chain != CHAIN_ID {
+                return Err(ContractError::WrongChain {
+                    expected: CHAIN_ID,
+                    actual: packet.chain,
+                });
+            }
+            handle_upgrade_contract(deps, env, &packet.payload)
+        }
+        2u8 => {
+            // chain 0 rotates the conductor for every contributor
+            if packet.chain != 0 && packet.chain != CHAIN_ID {
+                return Err(ContractError::WrongChain {
+                    expected: CHAIN_ID,
+                    actual: packet.chain,
+                });
+            }
+            handle_update_conductor(deps, env, &packet.payload)
+        }
+        action => Err(ContractError::InvalidVAAAction { action }),
+    }
+}
+
+fn handle_upgrade_contract(
+    _deps: DepsMut,
+    env: Env,
+    data: &[u8],
+) -> Result<Response, ContractError> {
+    let UpgradeContract { new_contract } = UpgradeContract::deserialize(data)?;
 
     Ok(Response::new()
         .add_message(CosmosMsg::Wasm(WasmMsg::Migrate {
@@ -323,21 +821,79 @@ pub fn handle_upgrade_contract(_deps: DepsMut, env: Env, data: &Vec<u8>) -> StdR
         }))
         .add_attribute("action", "contract_upgrade"))
 }
-*/
+
+fn handle_update_conductor(
+    deps: DepsMut,
+    _env: Env,
+    data: &[u8],
+) -> Result<Response, ContractError> {
+    let UpdateConductor {
+        conductor_chain,
+        conductor_address,
+    } = UpdateConductor::deserialize(data)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    cfg.conductor_chain = conductor_chain;
+    cfg.conductor_address = conductor_address;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_conductor")
+        .add_attribute("conductor_chain", cfg.conductor_chain.to_string())
+        .add_attribute("conductor_address", hex::encode(&cfg.conductor_address)))
+}
+
+fn verify_governance(
+    storage: &mut dyn Storage,
+    emitter_chain: u16,
+    emitter_address: &[u8],
+) -> Result<(), ContractError> {
+    let cfg = CONFIG.load(storage)?;
+
+    if cfg.gov_chain != emitter_chain || !cfg.gov_address.eq(emitter_address) {
+        return Err(ContractError::InvalidEmitter {
+            expected_chain: cfg.gov_chain,
+            expected_address: hex::encode(&cfg.gov_address),
+            actual_chain: emitter_chain,
+            actual_address: hex::encode(emitter_address),
+        });
+    }
+
+    Ok(())
+}
+
+// module name is left-padded with zeros to 32 bytes, like wormhole's own
+// governance modules
+fn governance_module() -> Vec<u8> {
+    let mut module = vec![0u8; 32 - GOVERNANCE_MODULE.len()];
+    module.extend(GOVERNANCE_MODULE.as_bytes());
+    module
+}
+
+// a transaction that fails after this point reverts the write, so the vaa
+// can still be resubmitted
+fn consume_vaa(storage: &mut dyn Storage, hash: &[u8]) -> Result<(), ContractError> {
+    if CONSUMED_VAAS.has(storage, hash) {
+        return Err(ContractError::VaaAlreadyExecuted);
+    }
+    CONSUMED_VAAS.save(storage, hash, &true)?;
+    Ok(())
+}
 
 fn verify_conductor(
     storage: &mut dyn Storage,
     emitter_chain: u16,
     emitter_address: &[u8],
-) -> StdResult<()> {
+) -> Result<(), ContractError> {
     let cfg = CONFIG.load(storage)?;
 
     if cfg.conductor_chain != emitter_chain || !cfg.conductor_address.eq(emitter_address) {
-        return Err(StdError::generic_err(format!(
-            "invalid emitter: {}:{}",
-            emitter_chain,
-            hex::encode(emitter_address),
-        )));
+        return Err(ContractError::InvalidEmitter {
+            expected_chain: cfg.conductor_chain,
+            expected_address: hex::encode(&cfg.conductor_address),
+            actual_chain: emitter_chain,
+            actual_address: hex::encode(emitter_address),
+        });
     }
 
     Ok(())
@@ -354,3 +910,245 @@ fn parse_vaa(deps: Deps, block_time: u64, data: &Binary) -> StdResult<ParsedVAA>
     }))?;
     Ok(vaa)
 }
+
+fn verify_kyc_signature(
+    deps: Deps,
+    sale: &SaleCore,
+    token_index: u8,
+    amount: Uint256,
+    buyer: &Addr,
+    prior_contribution: Uint256,
+    signature: &[u8],
+) -> Result<(), ContractError> {
+    if signature.len() != 65 {
+        return Err(ContractError::InvalidKycSignature);
+    }
+
+    // the buyer's canonical address is left-padded to 32 bytes, the same
+    // way the evm contributor pads msg.sender
+    let buyer = deps.api.addr_canonicalize(buyer.as_str())?;
+    if buyer.len() > 32 {
+        return Err(ContractError::InvalidKycSignature);
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let mut encoded: Vec<u8> = Vec::with_capacity(6 * 32);
+    encoded.extend(cfg.conductor_address.iter()); // 32
+    encoded.extend(sale.id.iter()); // 32
+    encoded.extend([0u8; 31].iter()); // 31 (zero padding u8)
+    encoded.push(token_index); // 1
+    encoded.extend(amount.to_be_bytes().iter()); // 32
+    encoded.extend(vec![0u8; 32 - buyer.len()]); // zero padding
+    encoded.extend(buyer.as_slice().iter()); // 32 with padding
+    encoded.extend(prior_contribution.to_be_bytes().iter()); // 32
+
+    let hash = Keccak256::digest(&encoded);
+    let recovered = deps
+        .api
+        .secp256k1_recover_pubkey(&hash, &signature[0..64], signature[64])
+        .map_err(|_| ContractError::InvalidKycSignature)?;
+
+    // ethereum address is the last 20 bytes of the hashed uncompressed public key
+    // (sans the 0x04 prefix)
+    let recovered = Keccak256::digest(&recovered[1..]);
+    if recovered[12..] != sale.authority[..] {
+        return Err(ContractError::InvalidKycSignature);
+    }
+
+    Ok(())
+}
+
+fn accepted_asset_info(
+    deps: Deps,
+    sale_id: &[u8],
+    token_index: u8,
+    token: &AcceptedToken,
+) -> Result<AssetInfo, ContractError> {
+    terra_asset_info(deps, token.address.as_slice()).map_err(|_| {
+        ContractError::InvalidAcceptedToken {
+            sale_id: hex::encode(sale_id),
+            token_index,
+        }
+    })
+}
+
+// Terra native denoms are encoded the same way the token bridge encodes them:
+// a leading 1 followed by the left-padded denom. Everything else is expected to
+// be a cw20 contract address.
+fn terra_asset_info(deps: Deps, address: &[u8]) -> StdResult<AssetInfo> {
+    if address.get_u8(0) == 1 {
+        let denom: Vec<u8> = address[1..]
+            .iter()
+            .skip_while(|&&byte| byte == 0)
+            .cloned()
+            .collect();
+        let denom = String::from_utf8(denom)
+            .map_err(|_| StdError::generic_err("invalid native denom encoding"))?;
+        return Ok(AssetInfo::NativeToken { denom });
+    }
+
+    let contract_addr = deps.api.addr_humanize(&address.get_address(0))?;
+    Ok(AssetInfo::Token {
+        contract_addr: contract_addr.to_string(),
+    })
+}
+
+// Native denoms have to be one Terra actually issues, cw20s have to answer a
+// TokenInfo query.
+fn is_valid_terra_asset(deps: Deps, address: &[u8]) -> bool {
+    match terra_asset_info(deps, address) {
+        Ok(AssetInfo::NativeToken { denom }) => TERRA_NATIVE_DENOMS.contains(&denom.as_str()),
+        Ok(AssetInfo::Token { contract_addr }) => query_token_info(deps, &contract_addr).is_ok(),
+        Err(_) => false,
+    }
+}
+
+// The sale token is either a Terra asset or, if it originates from another
+// chain, the cw20 the token bridge minted for it.
+fn sale_token_asset_info(deps: Deps, sale: &SaleCore) -> StdResult<AssetInfo> {
+    if sale.token_chain == CHAIN_ID {
+        return terra_asset_info(deps, sale.token_address.as_slice());
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let wrapped: WrappedRegistryResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: cfg.token_bridge_contract,
+            msg: to_binary(&TokenBridgeQueryMsg::WrappedRegistry {
+                chain: sale.token_chain,
+                address: Binary::from(sale.token_address.as_slice()),
+            })?,
+        }))?;
+    Ok(AssetInfo::Token {
+        contract_addr: wrapped.address,
+    })
+}
+
+// Allocations are denominated in the sale token's decimals on its native chain.
+// The token bridge truncates wrapped tokens to 8 decimals, so the amount needs
+// to be scaled down to what the cw20 actually uses.
+fn to_sale_token_amount(
+    deps: Deps,
+    sale: &SaleCore,
+    sale_token: &AssetInfo,
+    amount: Uint256,
+) -> Result<Uint128, ContractError> {
+    let decimals = match sale_token {
+        AssetInfo::NativeToken { .. } => sale.token_decimals,
+        AssetInfo::Token { contract_addr } => query_token_info(deps, contract_addr)?.decimals,
+    };
+
+    if sale.token_decimals <= decimals {
+        return to_uint128(amount);
+    }
+    let divisor = Uint256::from(10u128).pow((sale.token_decimals - decimals) as u32);
+    let amount = amount
+        .checked_div(divisor)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    to_uint128(amount)
+}
+
+fn query_token_info(deps: Deps, contract_addr: &str) -> StdResult<TokenInfoResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
+    }))
+}
+
+fn query_asset_balance(deps: Deps, asset_info: &AssetInfo, account: &Addr) -> StdResult<Uint128> {
+    match asset_info {
+        AssetInfo::NativeToken { denom } => {
+            let coin = deps.querier.query_balance(account, denom)?;
+            Ok(coin.amount)
+        }
+        AssetInfo::Token { contract_addr } => {
+            let response: BalanceResponse =
+                deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr: contract_addr.clone(),
+                    msg: to_binary(&Cw20QueryMsg::Balance {
+                        address: account.to_string(),
+                    })?,
+                }))?;
+            Ok(response.balance)
+        }
+    }
+}
+
+fn transfer_asset_msg(
+    asset_info: &AssetInfo,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    match asset_info {
+        AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount,
+            }],
+        })),
+        AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.clone(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        })),
+    }
+}
+
+// Native denoms have to be deposited with the token bridge before they can be
+// transferred, whereas cw20s are pulled by the token bridge itself.
+fn bridge_asset_msgs(
+    cfg: &Config,
+    asset_info: AssetInfo,
+    amount: Uint128,
+    recipient: &[u8],
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages: Vec<CosmosMsg> = Vec::with_capacity(2);
+    match &asset_info {
+        AssetInfo::NativeToken { denom } => {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: cfg.token_bridge_contract.clone(),
+                msg: to_binary(&TokenBridgeExecuteMsg::DepositTokens {})?,
+                funds: vec![Coin {
+                    denom: denom.clone(),
+                    amount,
+                }],
+            }));
+        }
+        AssetInfo::Token { contract_addr } => {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.clone(),
+                msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: cfg.token_bridge_contract.clone(),
+                    amount,
+                    expires: None,
+                })?,
+                funds: vec![],
+            }));
+        }
+    }
+
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: cfg.token_bridge_contract.clone(),
+        msg: to_binary(&TokenBridgeExecuteMsg::InitiateTransfer {
+            asset: Asset {
+                info: asset_info,
+                amount,
+            },
+            recipient_chain: cfg.conductor_chain,
+            recipient: Binary::from(recipient),
+            fee: Uint128::zero(),
+            nonce: WORMHOLE_NONCE,
+        })?,
+        funds: vec![],
+    }));
+    Ok(messages)
+}
+
+fn to_uint128(amount: Uint256) -> Result<Uint128, ContractError> {
+    Uint128::try_from(amount).map_err(|_| ContractError::AmountTooLarge)
+}
@@ -27,6 +27,7 @@ pub enum ExecuteMsg {
         sale_id: Binary,
         token_index: u8,
         amount: Uint256,
+        signature: Binary,
     },
     AttestContributions {
         sale_id: Binary,
@@ -45,6 +46,12 @@ pub enum ExecuteMsg {
         sale_id: Binary,
         token_index: u8,
     },
+    KycAuthorityUpdated {
+        data: Binary,
+    },
+    SubmitVaa {
+        data: Binary,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -55,12 +62,43 @@ pub struct MigrateMsg {}
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
-    SaleRegistry { sale_id: Binary },
-    SaleStatus { sale_id: Binary },
-    SaleTimes { sale_id: Binary },
-    AcceptedToken { sale_id: Binary, token_index: u8 },
-    TotalContribution { sale_id: Binary, token_index: u8 },
-    TotalAllocation { sale_id: Binary, token_index: u8 },
+    SaleRegistry {
+        sale_id: Binary,
+    },
+    SaleStatus {
+        sale_id: Binary,
+    },
+    SaleTimes {
+        sale_id: Binary,
+    },
+    AcceptedToken {
+        sale_id: Binary,
+        token_index: u8,
+    },
+    TotalContribution {
+        sale_id: Binary,
+        token_index: u8,
+    },
+    TotalAllocation {
+        sale_id: Binary,
+        token_index: u8,
+    },
+    BuyerStatus {
+        sale_id: Binary,
+        token_index: u8,
+        buyer: String,
+    },
+    AllSales {
+        start_after: Option<Binary>,
+        limit: Option<u32>,
+    },
+    AcceptedTokens {
+        sale_id: Binary,
+    },
+    BuyerContributions {
+        sale_id: Binary,
+        buyer: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -76,12 +114,11 @@ pub struct SaleRegistryResponse {
     pub id: Vec<u8>,
     pub token_address: Vec<u8>,
     pub token_chain: u16,
-    pub token_amount: Uint256,
-    pub min_raise: Uint256,
+    pub token_decimals: u8,
     pub sale_start: u64,
     pub sale_end: u64,
     pub recipient: Vec<u8>,
-    pub refund_recipient: Vec<u8>,
+    pub authority: Vec<u8>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -98,6 +135,7 @@ pub struct SaleTimesResponse {
     pub id: Vec<u8>,
     pub start: u64,
     pub end: u64,
+    pub unlock_allocation: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -125,3 +163,56 @@ pub struct TotalAllocationResponse {
     pub token_index: u8,
     pub amount: Uint256,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BuyerStatusResponse {
+    pub id: Vec<u8>,
+    pub token_index: u8,
+    pub buyer: String,
+    pub contribution: Uint256,
+    pub allocation_is_claimed: bool,
+    pub refund_is_claimed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AllSalesResponse {
+    pub sales: Vec<SaleRegistryResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AcceptedTokenTotals {
+    pub token_index: u8,
+    pub chain: u16,
+    pub address: Vec<u8>,
+    pub conversion_rate: Uint128,
+    pub total_contribution: Uint256,
+    pub total_allocation: Uint256,
+    pub total_excess_contribution: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AcceptedTokensResponse {
+    pub id: Vec<u8>,
+    pub tokens: Vec<AcceptedTokenTotals>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BuyerContribution {
+    pub token_index: u8,
+    pub contribution: Uint256,
+    pub allocation_is_claimed: bool,
+    pub refund_is_claimed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BuyerContributionsResponse {
+    pub id: Vec<u8>,
+    pub buyer: String,
+    pub contributions: Vec<BuyerContribution>,
+}
@@ -1,45 +1,68 @@
-use cosmwasm_std::{Binary, Deps, StdResult};
+use cosmwasm_std::{Binary, Deps, Order, StdResult};
+use cw_storage_plus::Bound;
 
 use crate::{
-    error::ContributorError,
+    error::ContractError,
     msg::{
-        AcceptedTokenResponse, ConfigResponse, SaleRegistryResponse, SaleStatusResponse,
-        SaleTimesResponse, TotalAllocationResponse, TotalContributionResponse,
+        AcceptedTokenResponse, AcceptedTokenTotals, AcceptedTokensResponse, AllSalesResponse,
+        BuyerContribution, BuyerContributionsResponse, BuyerStatusResponse, ConfigResponse,
+        SaleRegistryResponse, SaleStatusResponse, SaleTimesResponse, TotalAllocationResponse,
+        TotalContributionResponse,
     },
+    shared::SaleCore,
     state::{
-        load_accepted_token, load_total_allocation, load_total_contribution, CONFIG, SALES,
+        load_accepted_token, load_buyer_status, load_total_allocation, load_total_contribution,
+        load_total_excess_contribution, ACCEPTED_TOKENS, BUYER_STATUS, CONFIG, SALES,
         SALE_STATUSES, SALE_TIMES,
     },
 };
 
-pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
     match CONFIG.load(deps.storage) {
         Ok(cfg) => Ok(ConfigResponse {
             conductor_chain: cfg.conductor_chain,
             conductor_address: cfg.conductor_address,
         }),
-        Err(_) => ContributorError::NotInitialized.std_err(),
+        Err(_) => Err(ContractError::NotInitialized),
     }
 }
 
-pub fn query_sale_registry(deps: Deps, sale_id: &Binary) -> StdResult<SaleRegistryResponse> {
+pub fn query_sale_registry(
+    deps: Deps,
+    sale_id: &Binary,
+) -> Result<SaleRegistryResponse, ContractError> {
     match SALES.load(deps.storage, sale_id.as_slice()) {
-        Ok(sale) => Ok(SaleRegistryResponse {
-            id: sale.id,
-            token_address: sale.token_address,
-            token_chain: sale.token_chain,
-            token_amount: sale.token_amount,
-            min_raise: sale.min_raise,
-            sale_start: sale.times.start,
-            sale_end: sale.times.end,
-            recipient: sale.recipient,
-            refund_recipient: sale.refund_recipient,
+        Ok(sale) => Ok(to_sale_registry_response(sale)),
+        Err(_) => Err(ContractError::SaleNotFound {
+            sale_id: hex::encode(sale_id.as_slice()),
         }),
-        Err(_) => ContributorError::SaleNotFound.std_err(),
     }
 }
 
-pub fn query_sale_status(deps: Deps, sale_id: &Binary) -> StdResult<SaleStatusResponse> {
+pub fn query_all_sales(
+    deps: Deps,
+    start_after: Option<Binary>,
+    limit: Option<u32>,
+) -> Result<AllSalesResponse, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|sale_id| Bound::exclusive(sale_id.as_slice()));
+
+    let sales = SALES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, sale)| to_sale_registry_response(sale)))
+        .collect::<StdResult<Vec<SaleRegistryResponse>>>()?;
+    Ok(AllSalesResponse { sales })
+}
+
+pub fn query_sale_status(
+    deps: Deps,
+    sale_id: &Binary,
+) -> Result<SaleStatusResponse, ContractError> {
     let sale_id = sale_id.as_slice();
     match SALE_STATUSES.load(deps.storage, sale_id) {
         Ok(status) => Ok(SaleStatusResponse {
@@ -47,19 +70,24 @@ pub fn query_sale_status(deps: Deps, sale_id: &Binary) -> StdResult<SaleStatusRe
             is_sealed: status.is_sealed,
             is_aborted: status.is_aborted,
         }),
-        Err(_) => ContributorError::SaleStatusNotFound.std_err(),
+        Err(_) => Err(ContractError::SaleStatusNotFound {
+            sale_id: hex::encode(sale_id),
+        }),
     }
 }
 
-pub fn query_sale_times(deps: Deps, sale_id: &Binary) -> StdResult<SaleTimesResponse> {
+pub fn query_sale_times(deps: Deps, sale_id: &Binary) -> Result<SaleTimesResponse, ContractError> {
     let sale_id = sale_id.as_slice();
     match SALE_TIMES.load(deps.storage, sale_id) {
         Ok(times) => Ok(SaleTimesResponse {
             id: sale_id.to_vec(),
             start: times.start,
             end: times.end,
+            unlock_allocation: times.unlock_allocation,
+        }),
+        Err(_) => Err(ContractError::SaleTimesNotFound {
+            sale_id: hex::encode(sale_id),
         }),
-        Err(_) => ContributorError::SaleTimesNotFound.std_err(),
     }
 }
 
@@ -67,7 +95,7 @@ pub fn query_accepted_token(
     deps: Deps,
     sale_id: &Binary,
     token_index: u8,
-) -> StdResult<AcceptedTokenResponse> {
+) -> Result<AcceptedTokenResponse, ContractError> {
     let sale_id = sale_id.as_slice();
     match load_accepted_token(deps.storage, sale_id, token_index) {
         Ok(token) => Ok(AcceptedTokenResponse {
@@ -77,7 +105,10 @@ pub fn query_accepted_token(
             address: token.address,
             conversion_rate: token.conversion_rate,
         }),
-        Err(_) => ContributorError::AcceptedTokenNotFound.std_err(),
+        Err(_) => Err(ContractError::AcceptedTokenNotFound {
+            sale_id: hex::encode(sale_id),
+            token_index,
+        }),
     }
 }
 
@@ -85,7 +116,7 @@ pub fn query_total_contribution(
     deps: Deps,
     sale_id: &Binary,
     token_index: u8,
-) -> StdResult<TotalContributionResponse> {
+) -> Result<TotalContributionResponse, ContractError> {
     let sale_id = sale_id.as_slice();
     match load_total_contribution(deps.storage, sale_id, token_index) {
         Ok(amount) => Ok(TotalContributionResponse {
@@ -93,7 +124,10 @@ pub fn query_total_contribution(
             token_index,
             amount,
         }),
-        Err(_) => ContributorError::ContributionNotFound.std_err(),
+        Err(_) => Err(ContractError::ContributionNotFound {
+            sale_id: hex::encode(sale_id),
+            token_index,
+        }),
     }
 }
 
@@ -101,7 +135,7 @@ pub fn query_total_allocation(
     deps: Deps,
     sale_id: &Binary,
     token_index: u8,
-) -> StdResult<TotalAllocationResponse> {
+) -> Result<TotalAllocationResponse, ContractError> {
     let sale_id = sale_id.as_slice();
     match load_total_allocation(deps.storage, sale_id, token_index) {
         Ok(amount) => Ok(TotalAllocationResponse {
@@ -109,6 +143,125 @@ pub fn query_total_allocation(
             token_index,
             amount,
         }),
-        Err(_) => ContributorError::AllocationNotFound.std_err(),
+        Err(_) => Err(ContractError::AllocationNotFound {
+            sale_id: hex::encode(sale_id),
+            token_index,
+        }),
+    }
+}
+
+pub fn query_buyer_status(
+    deps: Deps,
+    sale_id: &Binary,
+    token_index: u8,
+    buyer: String,
+) -> Result<BuyerStatusResponse, ContractError> {
+    let sale_id = sale_id.as_slice();
+    let buyer = deps.api.addr_validate(buyer.as_str())?;
+    match load_buyer_status(deps.storage, sale_id, token_index, &buyer) {
+        Ok(status) => Ok(BuyerStatusResponse {
+            id: sale_id.to_vec(),
+            token_index,
+            buyer: buyer.to_string(),
+            contribution: status.contribution,
+            allocation_is_claimed: status.allocation_is_claimed,
+            refund_is_claimed: status.refund_is_claimed,
+        }),
+        Err(_) => Err(ContractError::BuyerStatusNotFound {
+            sale_id: hex::encode(sale_id),
+            token_index,
+        }),
+    }
+}
+
+pub fn query_accepted_tokens(
+    deps: Deps,
+    sale_id: &Binary,
+) -> Result<AcceptedTokensResponse, ContractError> {
+    let sale_id = sale_id.as_slice();
+    if !SALES.has(deps.storage, sale_id) {
+        return Err(ContractError::SaleNotFound {
+            sale_id: hex::encode(sale_id),
+        });
+    }
+
+    let mut tokens: Vec<AcceptedTokenTotals> = Vec::new();
+    for item in ACCEPTED_TOKENS
+        .prefix(sale_id)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (key, token) = item?;
+        let token_index = key[0];
+        tokens.push(AcceptedTokenTotals {
+            token_index,
+            chain: token.chain,
+            address: token.address,
+            conversion_rate: token.conversion_rate,
+            total_contribution: load_total_contribution(deps.storage, sale_id, token_index)?,
+            total_allocation: load_total_allocation(deps.storage, sale_id, token_index)?,
+            total_excess_contribution: load_total_excess_contribution(
+                deps.storage,
+                sale_id,
+                token_index,
+            )?,
+        });
+    }
+
+    Ok(AcceptedTokensResponse {
+        id: sale_id.to_vec(),
+        tokens,
+    })
+}
+
+pub fn query_buyer_contributions(
+    deps: Deps,
+    sale_id: &Binary,
+    buyer: String,
+) -> Result<BuyerContributionsResponse, ContractError> {
+    let sale_id = sale_id.as_slice();
+    if !SALES.has(deps.storage, sale_id) {
+        return Err(ContractError::SaleNotFound {
+            sale_id: hex::encode(sale_id),
+        });
+    }
+    let buyer = deps.api.addr_validate(buyer.as_str())?;
+
+    // buyer statuses are keyed by token index first, so walk the accepted
+    // tokens and skip the ones this buyer never contributed
+    let mut contributions: Vec<BuyerContribution> = Vec::new();
+    for key in ACCEPTED_TOKENS
+        .prefix(sale_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+    {
+        let token_index = key[0];
+        if let Some(status) =
+            BUYER_STATUS.may_load(deps.storage, (sale_id, token_index.into(), &buyer))?
+        {
+            contributions.push(BuyerContribution {
+                token_index,
+                contribution: status.contribution,
+                allocation_is_claimed: status.allocation_is_claimed,
+                refund_is_claimed: status.refund_is_claimed,
+            });
+        }
+    }
+
+    Ok(BuyerContributionsResponse {
+        id: sale_id.to_vec(),
+        buyer: buyer.to_string(),
+        contributions,
+    })
+}
+
+fn to_sale_registry_response(sale: SaleCore) -> SaleRegistryResponse {
+    SaleRegistryResponse {
+        id: sale.id,
+        token_address: sale.token_address,
+        token_chain: sale.token_chain,
+        token_decimals: sale.token_decimals,
+        sale_start: sale.times.start,
+        sale_end: sale.times.end,
+        recipient: sale.recipient,
+        authority: sale.authority,
     }
 }
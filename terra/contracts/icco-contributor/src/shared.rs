@@ -9,6 +9,7 @@ use wormhole::byte_utils::ByteUtils;
 pub struct SaleTimes {
     pub start: u64,
     pub end: u64,
+    pub unlock_allocation: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -16,30 +17,12 @@ pub struct SaleCore {
     pub id: Vec<u8>,
     pub token_address: Vec<u8>,
     pub token_chain: u16,
-    pub token_amount: Uint256,
-    pub min_raise: Uint256,
+    pub token_decimals: u8,
     pub times: SaleTimes,
     pub recipient: Vec<u8>,
-    pub refund_recipient: Vec<u8>,
+    pub authority: Vec<u8>,
 }
 
-/*
-impl Clone for SaleCore {
-    fn clone(&self) -> SaleCore {
-        SaleCore {
-            id: self.id.clone(),
-            token_address: self.token_address.clone(),
-            token_chain: self.token_chain,
-            token_amount: self.token_amount,
-            min_raise: self.min_raise,
-            times: self.times.clone(),
-            recipient: self.recipient.clone(),
-            refund_recipient: self.refund_recipient.clone(),
-        }
-    }
-}
-*/
-
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct AcceptedToken {
     pub chain: u16,
@@ -53,6 +36,17 @@ pub struct SaleStatus {
     pub is_aborted: bool,
 }
 
+// tracks what happened to the contributions of a locally native accepted
+// token once the sale is sealed. tokens that turn out not to exist on Terra
+// are marked invalid at init and never accept contributions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum AssetStatus {
+    Active,
+    Invalid,
+    NothingToTransfer,
+    TransferredToConductor,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Contribution {
     pub token_index: u8,
@@ -63,6 +57,7 @@ pub struct Contribution {
 pub struct Allocation {
     pub token_index: u8,
     pub allocated: Uint256,
+    pub excess_contributed: Uint256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -75,6 +70,7 @@ pub struct SaleInit {
 pub struct ContributionsSealed {
     pub sale_id: Vec<u8>,
     pub chain_id: u16,
+    pub solana_token_account: Vec<u8>,
     pub contributions: Vec<Contribution>,
 }
 
@@ -89,6 +85,12 @@ pub struct SaleAborted {
     pub sale_id: Vec<u8>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuthorityUpdated {
+    pub sale_id: Vec<u8>,
+    pub new_authority: Vec<u8>,
+}
+
 impl AcceptedToken {
     pub const N_BYTES: usize = 50;
 
@@ -105,14 +107,39 @@ impl AcceptedToken {
 
     pub fn serialize(&self) -> Vec<u8> {
         [
-            self.chain.to_be_bytes().to_vec(),
             self.address.to_vec(),
+            self.chain.to_be_bytes().to_vec(),
             self.conversion_rate.u128().to_be_bytes().to_vec(),
         ]
         .concat()
     }
 }
 
+impl Contribution {
+    pub const N_BYTES: usize = 33;
+
+    pub fn serialize(&self) -> Vec<u8> {
+        [
+            self.token_index.to_be_bytes().to_vec(),
+            self.contributed.to_be_bytes().to_vec(),
+        ]
+        .concat()
+    }
+}
+
+impl Allocation {
+    pub const N_BYTES: usize = 65;
+
+    pub fn serialize(&self) -> Vec<u8> {
+        [
+            self.token_index.to_be_bytes().to_vec(),
+            self.allocated.to_be_bytes().to_vec(),
+            self.excess_contributed.to_be_bytes().to_vec(),
+        ]
+        .concat()
+    }
+}
+
 pub fn to_const_bytes32(data: &[u8], index: usize) -> [u8; 32] {
     data.get_bytes32(index).get_const_bytes(0)
 }
@@ -121,18 +148,27 @@ pub fn to_u256(data: &[u8], index: usize) -> Uint256 {
     Uint256::new(to_const_bytes32(data, index))
 }
 
+// times are encoded as u256, but we only care about u64
+fn time_to_u256_bytes(time: u64) -> Vec<u8> {
+    Uint256::from(time).to_be_bytes().to_vec()
+}
+
 impl SaleInit {
     pub const PAYLOAD_ID: u8 = 1;
-    const INDEX_ACCEPTED_TOKENS_START: usize = 194;
+    const INDEX_ACCEPTED_TOKENS_START: usize = 131;
 
     pub fn deserialize(data: &[u8]) -> StdResult<Self> {
+        // fixed header + the accepted token count
+        if data.len() < SaleInit::INDEX_ACCEPTED_TOKENS_START + 1 {
+            return Err(StdError::generic_err("invalid SaleInit"));
+        }
+
         let sale_id = data.get_bytes32(0).to_vec();
         let token_address = data.get_bytes32(32).to_vec();
         let token_chain = data.get_u16(64);
-        let token_amount = to_u256(data, 66);
-        let min_raise = to_u256(data, 98);
-        let start = data.get_u64(130 + 24); // encoded as u256, but we only care about u64 time
-        let end = data.get_u64(162 + 24); // encoded as u256, but we only care about u64 for time
+        let token_decimals = data.get_u8(66);
+        let start = data.get_u64(67 + 24); // encoded as u256, but we only care about u64 time
+        let end = data.get_u64(99 + 24); // encoded as u256, but we only care about u64 for time
 
         let accepted_tokens =
             SaleInit::deserialize_tokens(&data[SaleInit::INDEX_ACCEPTED_TOKENS_START..])?;
@@ -141,19 +177,28 @@ impl SaleInit {
             + 1
             + AcceptedToken::N_BYTES * accepted_tokens.len();
 
+        // recipient (32) + authority (20) + unlock timestamp (32)
+        if data.len() != index + 84 {
+            return Err(StdError::generic_err("invalid SaleInit"));
+        }
+
         let recipient = data.get_bytes32(index).to_vec();
-        let refund_recipient = data.get_bytes32(index + 32).to_vec();
+        let authority = data.get_bytes(index + 32, 20).to_vec();
+        let unlock_allocation = data.get_u64(index + 52 + 24); // encoded as u256 too
 
         Ok(SaleInit {
             core: SaleCore {
                 id: sale_id,
                 token_address,
                 token_chain,
-                token_amount,
-                min_raise,
-                times: SaleTimes { start, end },
+                token_decimals,
+                times: SaleTimes {
+                    start,
+                    end,
+                    unlock_allocation,
+                },
                 recipient,
-                refund_recipient,
+                authority,
             },
             accepted_tokens,
         })
@@ -174,6 +219,32 @@ impl SaleInit {
         }
         Ok(tokens)
     }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let core = &self.core;
+        [
+            SaleInit::PAYLOAD_ID.to_be_bytes().to_vec(),
+            core.id.to_vec(),
+            core.token_address.to_vec(),
+            core.token_chain.to_be_bytes().to_vec(),
+            core.token_decimals.to_be_bytes().to_vec(),
+            time_to_u256_bytes(core.times.start),
+            time_to_u256_bytes(core.times.end),
+            self.serialize_tokens(),
+            core.recipient.to_vec(),
+            core.authority.to_vec(),
+            time_to_u256_bytes(core.times.unlock_allocation),
+        ]
+        .concat()
+    }
+
+    fn serialize_tokens(&self) -> Vec<u8> {
+        let mut encoded = (self.accepted_tokens.len() as u8).to_be_bytes().to_vec();
+        for token in self.accepted_tokens.iter() {
+            encoded.extend(token.serialize());
+        }
+        encoded
+    }
 }
 
 impl ContributionsSealed {
@@ -183,14 +254,16 @@ impl ContributionsSealed {
         ContributionsSealed {
             sale_id: sale_id.to_vec(),
             chain_id,
+            // only contributors on Solana need to specify a token account
+            solana_token_account: vec![0u8; 32],
             contributions: Vec::new(),
         }
     }
 
     pub fn add_contribution(&mut self, token_index: u8, contributed: Uint256) -> StdResult<usize> {
         // limit to len 255
-        if self.contributions.len() >= 256 {
-            return Err(StdError::generic_err("cannot exceed length 256"));
+        if self.contributions.len() >= 255 {
+            return Err(StdError::generic_err("cannot exceed length 255"));
         }
 
         self.contributions.push(Contribution {
@@ -199,91 +272,163 @@ impl ContributionsSealed {
         });
         Ok(self.contributions.len())
     }
-    /*
-    pub fn deserialize(data: &Vec<u8>) -> StdResult<Self> {
-        let data = data.as_slice();
-        let payload_id = data.get_u8(0);
-
-        if payload_id != ContributionsSealed::PAYLOAD_ID {
-            return Err(StdError::generic_err(
-                "payload_id != ContributionsSealed::PAYLOAD_ID"
-            ));
+
+    pub fn deserialize(data: &[u8]) -> StdResult<Self> {
+        if data.len() < 67 {
+            return Err(StdError::generic_err("invalid ContributionsSealed"));
+        }
+
+        let sale_id = data.get_bytes32(0).to_vec();
+        let chain_id = data.get_u16(32);
+        let solana_token_account = data.get_bytes32(34).to_vec();
+
+        let n_contributions = data.get_u8(66) as usize;
+        if data.len() != 67 + Contribution::N_BYTES * n_contributions {
+            return Err(StdError::generic_err("invalid ContributionsSealed"));
+        }
+
+        let mut contributions: Vec<Contribution> = Vec::with_capacity(n_contributions);
+        for i in 0..n_contributions {
+            let start = 67 + Contribution::N_BYTES * i;
+            contributions.push(Contribution {
+                token_index: data.get_u8(start),
+                contributed: to_u256(data, start + 1),
+            });
         }
-        let sale_id = data.get_bytes32(1).to_vec();
-        let chain_id = data.get_u16(33);
 
-        // TODO: deserialize
-        let contributions: Vec<Contribution> = Vec::new();
         Ok(ContributionsSealed {
             sale_id,
             chain_id,
+            solana_token_account,
             contributions,
         })
     }
-    */
+
     pub fn serialize(&self) -> Vec<u8> {
         [
             ContributionsSealed::PAYLOAD_ID.to_be_bytes().to_vec(),
             self.sale_id.to_vec(),
             self.chain_id.to_be_bytes().to_vec(),
+            self.solana_token_account.to_vec(),
             self.serialize_contributions(),
         ]
         .concat()
     }
 
     fn serialize_contributions(&self) -> Vec<u8> {
-        // TODO
-        Vec::new()
+        let mut encoded = (self.contributions.len() as u8).to_be_bytes().to_vec();
+        for contribution in self.contributions.iter() {
+            encoded.extend(contribution.serialize());
+        }
+        encoded
     }
 }
 
 impl SaleSealed {
     pub const PAYLOAD_ID: u8 = 3;
 
-    pub fn add_allocation(&mut self, token_index: u8, allocated: Uint256) -> StdResult<u8> {
+    pub fn add_allocation(
+        &mut self,
+        token_index: u8,
+        allocated: Uint256,
+        excess_contributed: Uint256,
+    ) -> StdResult<u8> {
         self.allocations.push(Allocation {
             token_index,
             allocated,
+            excess_contributed,
         });
         Ok(token_index)
     }
     pub fn deserialize(data: &[u8]) -> StdResult<Self> {
+        // sale id + the allocation count
+        if data.len() < 33 {
+            return Err(StdError::generic_err("invalid SaleSealed"));
+        }
+
         let sale_id = data.get_bytes32(0).to_vec();
 
-        let allocations = SaleSealed::deserialize_allocations(&data[32..]);
+        let allocations = SaleSealed::deserialize_allocations(&data[32..])?;
         Ok(SaleSealed {
             sale_id,
             allocations,
         })
     }
 
-    pub fn deserialize_allocations(data: &[u8]) -> Vec<Allocation> {
-        // TODO
-        Vec::new()
+    pub fn deserialize_allocations(data: &[u8]) -> StdResult<Vec<Allocation>> {
+        let n_allocations = data.get_u8(0) as usize;
+        if data.len() != 1 + Allocation::N_BYTES * n_allocations {
+            return Err(StdError::generic_err("invalid SaleSealed"));
+        }
+
+        let mut allocations: Vec<Allocation> = Vec::with_capacity(n_allocations);
+        for i in 0..n_allocations {
+            let start = 1 + Allocation::N_BYTES * i;
+            allocations.push(Allocation {
+                token_index: data.get_u8(start),
+                allocated: to_u256(data, start + 1),
+                excess_contributed: to_u256(data, start + 33),
+            });
+        }
+        Ok(allocations)
     }
-    /*
+
     pub fn serialize(&self) -> Vec<u8> {
-        [
-            SaleSealed::PAYLOAD_IDto_be_bytes().to_vec(),
-            self.sale_id.clone(),
-            // TODO: serialize allocations
+        let mut encoded = [
+            SaleSealed::PAYLOAD_ID.to_be_bytes().to_vec(),
+            self.sale_id.to_vec(),
+            (self.allocations.len() as u8).to_be_bytes().to_vec(),
         ]
-        .concat()
+        .concat();
+        for allocation in self.allocations.iter() {
+            encoded.extend(allocation.serialize());
+        }
+        encoded
     }
-    */
 }
 
 impl SaleAborted {
     pub const PAYLOAD_ID: u8 = 4;
 
     pub fn deserialize(data: &[u8]) -> StdResult<Self> {
+        if data.len() != 32 {
+            return Err(StdError::generic_err("invalid SaleAborted"));
+        }
+
         Ok(SaleAborted {
             sale_id: data.get_bytes32(0).to_vec(),
         })
     }
-    /*
+
     pub fn serialize(&self) -> Vec<u8> {
-        [self.payload_id.to_be_bytes().to_vec(), self.sale_id.clone()].concat()
+        [
+            SaleAborted::PAYLOAD_ID.to_be_bytes().to_vec(),
+            self.sale_id.to_vec(),
+        ]
+        .concat()
+    }
+}
+
+impl AuthorityUpdated {
+    pub const PAYLOAD_ID: u8 = 6;
+
+    pub fn deserialize(data: &[u8]) -> StdResult<Self> {
+        if data.len() != 52 {
+            return Err(StdError::generic_err("invalid AuthorityUpdated"));
+        }
+
+        Ok(AuthorityUpdated {
+            sale_id: data.get_bytes32(0).to_vec(),
+            new_authority: data.get_bytes(32, 20).to_vec(),
+        })
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        [
+            AuthorityUpdated::PAYLOAD_ID.to_be_bytes().to_vec(),
+            self.sale_id.to_vec(),
+            self.new_authority.to_vec(),
+        ]
+        .concat()
     }
-    */
 }
@@ -1,10 +1,13 @@
-use cosmwasm_std::{StdResult, Storage, Uint256};
+use cosmwasm_std::{Addr, StdError, StdResult, Storage, Uint128, Uint256};
 use cw_storage_plus::{Item, Map, U8Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use wormhole::byte_utils::ByteUtils;
 
-use crate::shared::{AcceptedToken, SaleCore, SaleStatus, SaleTimes};
+use crate::{
+    error::ContractError,
+    shared::{AcceptedToken, AssetStatus, SaleCore, SaleStatus, SaleTimes},
+};
 
 // per sale_id and token_index, we need to track a buyer's contribution, as well as whether
 // he has been refunded or his allocations have been claimed
@@ -17,38 +20,60 @@ pub struct BuyerStatus {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
+    pub gov_chain: u16,
+    pub gov_address: Vec<u8>,
     pub wormhole_contract: HumanAddr,
     pub token_bridge_contract: HumanAddr,
     pub conductor_chain: u16,
     pub conductor_address: Vec<u8>,
 }
 
+// a cw20 contribution waiting on its transfer to complete. the buyer is
+// credited with the balance difference once the reply comes back
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingContribution {
+    pub sale_id: Vec<u8>,
+    pub token_index: u8,
+    pub buyer: Addr,
+    pub token: HumanAddr,
+    pub amount: Uint128,
+    pub balance_before: Uint128,
+}
+
 pub struct SaleMessage<'a> {
     pub id: u8,
     pub payload: &'a [u8],
 }
 
-/*
 pub struct UpgradeContract {
     pub new_contract: u64,
 }
-*/
+
+pub struct UpdateConductor {
+    pub conductor_chain: u16,
+    pub conductor_address: Vec<u8>,
+}
 
 pub type HumanAddr = String;
 pub type SaleId<'a> = &'a [u8];
 pub type TokenIndexKey<'a> = (SaleId<'a>, U8Key);
-pub type BuyerTokenIndexKey<'a> = (SaleId<'a>, U8Key, &'a HumanAddr);
+pub type BuyerTokenIndexKey<'a> = (SaleId<'a>, U8Key, &'a Addr);
 
 pub const CHAIN_ID: u16 = 3;
 
 pub const CONFIG: Item<Config> = Item::new("config");
+pub const CONSUMED_VAAS: Map<&[u8], bool> = Map::new("consumed_vaas");
+pub const PENDING_CONTRIBUTION: Item<PendingContribution> = Item::new("pending_contribution");
 pub const SALES: Map<SaleId, SaleCore> = Map::new("sales");
 pub const SALE_STATUSES: Map<SaleId, SaleStatus> = Map::new("sale_statuses");
 pub const SALE_TIMES: Map<SaleId, SaleTimes> = Map::new("sale_times");
 pub const ACCEPTED_TOKENS: Map<(SaleId, U8Key), AcceptedToken> = Map::new("accepted_tokens");
 pub const TOTAL_CONTRIBUTIONS: Map<TokenIndexKey, Uint256> = Map::new("total_contributions");
 pub const TOTAL_ALLOCATIONS: Map<TokenIndexKey, Uint256> = Map::new("total_allocations");
-//const BUYER_STATUS: Map<BuyerTokenIndexKey, BuyerStatus> = Map::new("buyer_statuses");
+pub const TOTAL_EXCESS_CONTRIBUTIONS: Map<TokenIndexKey, Uint256> =
+    Map::new("total_excess_contributions");
+pub const ASSET_STATUSES: Map<TokenIndexKey, AssetStatus> = Map::new("asset_statuses");
+pub const BUYER_STATUS: Map<BuyerTokenIndexKey, BuyerStatus> = Map::new("buyer_statuses");
 
 pub const ZERO_AMOUNT: Uint256 = Uint256::zero();
 
@@ -76,64 +101,146 @@ pub fn load_total_allocation(
     TOTAL_ALLOCATIONS.load(storage, (sale_id, token_index.into()))
 }
 
-/*
-pub fn update_contribution(
-    storage: &mut dyn Storage,
-    block_time: u64,
+pub fn load_total_excess_contribution(
+    storage: &dyn Storage,
+    sale_id: &[u8],
+    token_index: u8,
+) -> StdResult<Uint256> {
+    TOTAL_EXCESS_CONTRIBUTIONS.load(storage, (sale_id, token_index.into()))
+}
+
+pub fn load_buyer_status(
+    storage: &dyn Storage,
     sale_id: &[u8],
     token_index: u8,
-    buyer: &HumanAddr,
-    contribution: Uint256,
+    buyer: &Addr,
 ) -> StdResult<BuyerStatus> {
-    let token_index = U8Key::from(token_index);
-
-    // check the status of the sale
-    let status = SALE_STATUSES.load(storage, sale_id.to_vec())?;
-    if status.is_aborted {
-        return Err(StdError::generic_err("sale was aborted"));
-    } else if status.is_sealed {
-        return Err(StdError::generic_err("sale was sealed"));
-    }
+    BUYER_STATUS.load(storage, (sale_id, token_index.into(), buyer))
+}
 
-    // is the sale still happening?
-    let times = SALE_TIMES.load(storage, sale_id.to_vec())?;
-    if block_time < times.start {
-        return Err(StdError::generic_err("sale has not started yet"));
-    } else if block_time > times.end {
-        return Err(StdError::generic_err("sale has ended"));
+pub fn load_buyer_contribution(
+    storage: &dyn Storage,
+    sale_id: &[u8],
+    token_index: u8,
+    buyer: &Addr,
+) -> StdResult<Uint256> {
+    let key: BuyerTokenIndexKey = (sale_id, token_index.into(), buyer);
+    match BUYER_STATUS.may_load(storage, key)? {
+        Some(status) => Ok(status.contribution),
+        None => Ok(ZERO_AMOUNT),
     }
+}
 
+pub fn update_buyer_contribution(
+    storage: &mut dyn Storage,
+    sale_id: &[u8],
+    token_index: u8,
+    buyer: &Addr,
+    amount: Uint256,
+) -> Result<BuyerStatus, ContractError> {
     // update the buyer's contributions for a particular token
-    let buyer_key: BuyerTokenIndexKey = (sale_id.to_vec(), token_index.clone(), buyer.clone());
-    let action = |d: Option<BuyerStatus>| -> StdResult<BuyerStatus> {
-        match d {
-            Some(status) => Ok(BuyerStatus {
-                contribution: status.contribution + contribution,
-                refund_is_claimed: status.refund_is_claimed,
-                allocation_is_claimed: status.allocation_is_claimed,
-            }),
-            None => Ok(BuyerStatus {
-                contribution,
-                refund_is_claimed: false,
-                allocation_is_claimed: false,
-            }),
-        }
-    };
-    let buyer_status = BUYER_STATUS.update(storage, buyer_key, action)?;
+    let buyer_key: BuyerTokenIndexKey = (sale_id, token_index.into(), buyer);
+    let buyer_status = BUYER_STATUS.update(
+        storage,
+        buyer_key,
+        |status: Option<BuyerStatus>| -> Result<BuyerStatus, ContractError> {
+            match status {
+                Some(status) => Ok(BuyerStatus {
+                    contribution: status.contribution.checked_add(amount)?,
+                    allocation_is_claimed: status.allocation_is_claimed,
+                    refund_is_claimed: status.refund_is_claimed,
+                }),
+                None => Ok(BuyerStatus {
+                    contribution: amount,
+                    allocation_is_claimed: false,
+                    refund_is_claimed: false,
+                }),
+            }
+        },
+    )?;
 
     // now update total contributions
-    let token_key: TokenIndexKey = (sale_id.to_vec(), token_index.clone());
-    let action = |d: Option<Uint256>| -> StdResult<Uint256> {
-        match d {
-            Some(current) => Ok(current + contribution),
-            None => Err(StdError::generic_err("shouldn't be here")),
-        }
-    };
-    TOTAL_CONTRIBUTIONS.update(storage, token_key, action)?;
+    let token_key: TokenIndexKey = (sale_id, token_index.into());
+    TOTAL_CONTRIBUTIONS.update(
+        storage,
+        token_key,
+        |total: Option<Uint256>| -> Result<Uint256, ContractError> {
+            match total {
+                Some(total) => Ok(total.checked_add(amount)?),
+                None => Err(ContractError::ContributionNotFound {
+                    sale_id: hex::encode(sale_id),
+                    token_index,
+                }),
+            }
+        },
+    )?;
 
     Ok(buyer_status)
 }
-*/
+
+pub fn claim_buyer_allocation(
+    storage: &mut dyn Storage,
+    sale_id: &[u8],
+    token_index: u8,
+    buyer: &Addr,
+) -> Result<BuyerStatus, ContractError> {
+    let buyer_key: BuyerTokenIndexKey = (sale_id, token_index.into(), buyer);
+    BUYER_STATUS.update(
+        storage,
+        buyer_key,
+        |status: Option<BuyerStatus>| -> Result<BuyerStatus, ContractError> {
+            match status {
+                Some(status) if status.allocation_is_claimed => {
+                    Err(ContractError::AllocationAlreadyClaimed {
+                        sale_id: hex::encode(sale_id),
+                        token_index,
+                    })
+                }
+                Some(status) => Ok(BuyerStatus {
+                    contribution: status.contribution,
+                    allocation_is_claimed: true,
+                    refund_is_claimed: status.refund_is_claimed,
+                }),
+                None => Err(ContractError::BuyerStatusNotFound {
+                    sale_id: hex::encode(sale_id),
+                    token_index,
+                }),
+            }
+        },
+    )
+}
+
+pub fn claim_buyer_refund(
+    storage: &mut dyn Storage,
+    sale_id: &[u8],
+    token_index: u8,
+    buyer: &Addr,
+) -> Result<BuyerStatus, ContractError> {
+    let buyer_key: BuyerTokenIndexKey = (sale_id, token_index.into(), buyer);
+    BUYER_STATUS.update(
+        storage,
+        buyer_key,
+        |status: Option<BuyerStatus>| -> Result<BuyerStatus, ContractError> {
+            match status {
+                Some(status) if status.refund_is_claimed => {
+                    Err(ContractError::RefundAlreadyClaimed {
+                        sale_id: hex::encode(sale_id),
+                        token_index,
+                    })
+                }
+                Some(status) => Ok(BuyerStatus {
+                    contribution: status.contribution,
+                    allocation_is_claimed: status.allocation_is_claimed,
+                    refund_is_claimed: true,
+                }),
+                None => Err(ContractError::BuyerStatusNotFound {
+                    sale_id: hex::encode(sale_id),
+                    token_index,
+                }),
+            }
+        },
+    )
+}
 
 impl<'a> SaleMessage<'a> {
     pub fn deserialize(data: &'a [u8]) -> StdResult<Self> {
@@ -144,12 +251,26 @@ impl<'a> SaleMessage<'a> {
     }
 }
 
-/*
 impl UpgradeContract {
-    pub fn deserialize(data: &Vec<u8>) -> StdResult<Self> {
-        let data = data.as_slice();
+    pub fn deserialize(data: &[u8]) -> StdResult<Self> {
+        if data.len() != 32 {
+            return Err(StdError::generic_err("invalid UpgradeContract"));
+        }
         let new_contract = data.get_u64(24);
         Ok(UpgradeContract { new_contract })
     }
 }
-*/
+
+impl UpdateConductor {
+    pub fn deserialize(data: &[u8]) -> StdResult<Self> {
+        if data.len() != 34 {
+            return Err(StdError::generic_err("invalid UpdateConductor"));
+        }
+        let conductor_chain = data.get_u16(0);
+        let conductor_address = data.get_bytes32(2).to_vec();
+        Ok(UpdateConductor {
+            conductor_chain,
+            conductor_address,
+        })
+    }
+}
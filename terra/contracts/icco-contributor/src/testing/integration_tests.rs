@@ -0,0 +1,1007 @@
+use crate::{
+    error::ContractError,
+    msg::{
+        AcceptedTokenResponse, AcceptedTokenTotals, AcceptedTokensResponse, AllSalesResponse,
+        BuyerContribution, BuyerContributionsResponse, BuyerStatusResponse, ConfigResponse,
+        ExecuteMsg, InstantiateMsg, QueryMsg, SaleRegistryResponse, SaleStatusResponse,
+        SaleTimesResponse, TotalAllocationResponse, TotalContributionResponse,
+    },
+    shared::{
+        AcceptedToken, ContributionsSealed, SaleAborted, SaleCore, SaleInit, SaleSealed, SaleTimes,
+    },
+    testing::{
+        mock_contracts::{
+            contributor_contract, cw20_contract, token_bridge_contract, wormhole_contract,
+            MockTokenBridgeInstantiateMsg, MockWormholeInstantiateMsg,
+        },
+        mock_querier::TerraMockApi,
+    },
+};
+use cosmwasm_std::testing::{mock_env, MockStorage};
+use cosmwasm_std::{coins, Addr, Api, Binary, Event, Uint128, Uint256};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_multi_test::{App, AppResponse, BankKeeper, Executor};
+use k256::ecdsa::{recoverable, signature::DigestSigner, SigningKey};
+use sha3::{Digest, Keccak256};
+
+const OWNER: &str = "owner";
+const BUYER_ONE: &str = "buyerone";
+const BUYER_TWO: &str = "buyertwo";
+const RELAYER: &str = "relayer";
+
+const DENOM: &str = "uluna";
+const STARTING_BALANCE: u128 = 10_000_000;
+
+const CONDUCTOR_CHAIN: u16 = 2;
+const CONDUCTOR_ADDRESS: &str = "0000000000000000000000005f8e26facc23fa4cbd87b8d9dbbd33d5047abde1";
+const GOV_CHAIN: u16 = 1;
+const GOV_ADDRESS: &str = "0000000000000000000000000000000000000000000000000000000000000004";
+
+// the sale token lives on the conductor's chain and reaches Terra through the
+// token bridge
+const SALE_TOKEN_ADDRESS: &str = "00000000000000000000000083752ecafebf4707258dedffbd9c7443148169db";
+const SALE_TOKEN_DECIMALS: u8 = 18;
+const WRAPPED_DECIMALS: u8 = 8;
+const RECIPIENT: &str = "00000000000000000000000022d491bde2303f2f43325b2108d26f1eaba1e32b";
+const FOREIGN_TOKEN_ADDRESS: &str =
+    "000000000000000000000000ddb64fe46a91d46ee29420539fc25fd07c5fea3e";
+
+// private keys are only used to sign vaas and kyc approvals locally
+const GUARDIAN_KEY: &str = "cfb12303a19cde580bb4dd771639b0d26bc68353645571a8cff516ab2ee113a0";
+const GUARDIAN_ADDRESS: &str = "befa429d57cd18b7f8a4d91a2da9ab4af05d0fbe";
+const KYC_KEY: &str = "b0057716d5917badaf911b193b12b910811c1497b5bada8d7711f758981c3773";
+const KYC_AUTHORITY: &str = "1df62f291b2e969fb0849d99d9ce41e2f137006e";
+
+const SALE_START: u64 = 100;
+const SALE_END: u64 = 200;
+const UNLOCK_ALLOCATION: u64 = 300;
+
+struct Suite {
+    app: App,
+    wormhole: Addr,
+    token_bridge: Addr,
+    sale_token: Addr,
+    accepted_cw20: Addr,
+    contributor: Addr,
+    sequence: u64,
+}
+
+impl Suite {
+    fn new() -> Suite {
+        let mut app = App::new(
+            TerraMockApi::default(),
+            mock_env().block,
+            BankKeeper::new(),
+            MockStorage::new(),
+        );
+        for buyer in [BUYER_ONE, BUYER_TWO].iter() {
+            app.init_bank_balance(&Addr::unchecked(*buyer), coins(STARTING_BALANCE, DENOM))
+                .unwrap();
+        }
+
+        let wormhole_code_id = app.store_code(wormhole_contract());
+        let token_bridge_code_id = app.store_code(token_bridge_contract());
+        let cw20_code_id = app.store_code(cw20_contract());
+        let contributor_code_id = app.store_code(contributor_contract());
+
+        let owner = Addr::unchecked(OWNER);
+        let wormhole = app
+            .instantiate_contract(
+                wormhole_code_id,
+                owner.clone(),
+                &MockWormholeInstantiateMsg {
+                    guardian: Binary::from(hex::decode(GUARDIAN_ADDRESS).unwrap()),
+                },
+                &[],
+                "wormhole",
+                None,
+            )
+            .unwrap();
+
+        // the owner stands in for the conductor, which sends the sale token
+        // to the contributor before the sale is sealed
+        let sale_token = instantiate_cw20(
+            &mut app,
+            cw20_code_id,
+            "Wrapped Sale Token",
+            WRAPPED_DECIMALS,
+            vec![Cw20Coin {
+                address: OWNER.to_string(),
+                amount: Uint128::new(100_000_000_000),
+            }],
+        );
+        let accepted_cw20 = instantiate_cw20(
+            &mut app,
+            cw20_code_id,
+            "Accepted Token",
+            6,
+            vec![Cw20Coin {
+                address: BUYER_TWO.to_string(),
+                amount: Uint128::new(STARTING_BALANCE),
+            }],
+        );
+
+        let token_bridge = app
+            .instantiate_contract(
+                token_bridge_code_id,
+                owner.clone(),
+                &MockTokenBridgeInstantiateMsg {
+                    wrapped_asset: sale_token.to_string(),
+                },
+                &[],
+                "token bridge",
+                None,
+            )
+            .unwrap();
+
+        let contributor = app
+            .instantiate_contract(
+                contributor_code_id,
+                owner,
+                &InstantiateMsg {
+                    gov_chain: GOV_CHAIN,
+                    gov_address: Binary::from(hex::decode(GOV_ADDRESS).unwrap()),
+                    wormhole_contract: wormhole.to_string(),
+                    token_bridge_contract: token_bridge.to_string(),
+                    conductor_chain: CONDUCTOR_CHAIN,
+                    conductor_address: Binary::from(hex::decode(CONDUCTOR_ADDRESS).unwrap()),
+                },
+                &[],
+                "icco contributor",
+                None,
+            )
+            .unwrap();
+
+        Suite {
+            app,
+            wormhole,
+            token_bridge,
+            sale_token,
+            accepted_cw20,
+            contributor,
+            sequence: 0,
+        }
+    }
+
+    // sale times are relative to when the suite was created
+    fn set_time(&mut self, offset: u64) {
+        let start = mock_env().block.time;
+        self.app.update_block(|block| {
+            block.time = start.plus_seconds(offset);
+            block.height += 1;
+        });
+    }
+
+    // token 0 is the native denom, token 1 the accepted cw20 and token 2 lives
+    // on the conductor's chain
+    fn sale_init(&self, sale_id: u8) -> SaleInit {
+        let start = mock_env().block.time.seconds();
+        SaleInit {
+            core: SaleCore {
+                id: sale_id_bytes(sale_id),
+                token_address: hex::decode(SALE_TOKEN_ADDRESS).unwrap(),
+                token_chain: CONDUCTOR_CHAIN,
+                token_decimals: SALE_TOKEN_DECIMALS,
+                times: SaleTimes {
+                    start: start + SALE_START,
+                    end: start + SALE_END,
+                    unlock_allocation: start + UNLOCK_ALLOCATION,
+                },
+                recipient: hex::decode(RECIPIENT).unwrap(),
+                authority: hex::decode(KYC_AUTHORITY).unwrap(),
+            },
+            accepted_tokens: vec![
+                AcceptedToken {
+                    chain: 3,
+                    address: native_address(DENOM),
+                    conversion_rate: Uint128::new(1_000_000_000_000_000_000),
+                },
+                AcceptedToken {
+                    chain: 3,
+                    address: cw20_address(&self.accepted_cw20),
+                    conversion_rate: Uint128::new(2_000_000_000_000_000_000),
+                },
+                AcceptedToken {
+                    chain: CONDUCTOR_CHAIN,
+                    address: hex::decode(FOREIGN_TOKEN_ADDRESS).unwrap(),
+                    conversion_rate: Uint128::new(1_000_000_000_000_000_000),
+                },
+            ],
+        }
+    }
+
+    fn conductor_vaa(&mut self, payload: &[u8]) -> Binary {
+        let sequence = self.sequence;
+        self.sequence += 1;
+        signed_vaa(
+            GUARDIAN_KEY,
+            CONDUCTOR_CHAIN,
+            &hex::decode(CONDUCTOR_ADDRESS).unwrap(),
+            sequence,
+            payload,
+        )
+    }
+
+    fn execute(
+        &mut self,
+        sender: &str,
+        msg: &ExecuteMsg,
+        funds: u128,
+    ) -> Result<AppResponse, ContractError> {
+        let funds = match funds {
+            0 => vec![],
+            amount => coins(amount, DENOM),
+        };
+        self.app
+            .execute_contract(
+                Addr::unchecked(sender),
+                self.contributor.clone(),
+                msg,
+                &funds,
+            )
+            .map_err(|err| err.downcast().unwrap())
+    }
+
+    fn init_sale(&mut self, sale_id: u8) -> Result<AppResponse, ContractError> {
+        let data = self.conductor_vaa(&self.sale_init(sale_id).serialize());
+        self.execute(RELAYER, &ExecuteMsg::InitSale { data }, 0)
+    }
+
+    fn contribute(
+        &mut self,
+        buyer: &str,
+        sale_id: u8,
+        token_index: u8,
+        amount: u128,
+        prior_contribution: u128,
+    ) -> Result<AppResponse, ContractError> {
+        let msg = ExecuteMsg::Contribute {
+            sale_id: Binary::from(sale_id_bytes(sale_id)),
+            token_index,
+            amount: Uint256::from(amount),
+            signature: kyc_signature(sale_id, token_index, amount, buyer, prior_contribution),
+        };
+
+        // cw20 contributions are pulled by the contributor instead
+        match token_index {
+            0 => self.execute(buyer, &msg, amount),
+            _ => {
+                self.app
+                    .execute_contract(
+                        Addr::unchecked(buyer),
+                        self.accepted_cw20.clone(),
+                        &Cw20ExecuteMsg::IncreaseAllowance {
+                            spender: self.contributor.to_string(),
+                            amount: Uint128::new(amount),
+                            expires: None,
+                        },
+                        &[],
+                    )
+                    .unwrap();
+                self.execute(buyer, &msg, 0)
+            }
+        }
+    }
+
+    fn claim_allocation(
+        &mut self,
+        buyer: &str,
+        sale_id: u8,
+        token_index: u8,
+    ) -> Result<AppResponse, ContractError> {
+        let msg = ExecuteMsg::ClaimAllocation {
+            sale_id: Binary::from(sale_id_bytes(sale_id)),
+            token_index,
+        };
+        self.execute(buyer, &msg, 0)
+    }
+
+    fn claim_refund(
+        &mut self,
+        buyer: &str,
+        sale_id: u8,
+        token_index: u8,
+    ) -> Result<AppResponse, ContractError> {
+        let msg = ExecuteMsg::ClaimRefund {
+            sale_id: Binary::from(sale_id_bytes(sale_id)),
+            token_index,
+        };
+        self.execute(buyer, &msg, 0)
+    }
+
+    fn query<T: serde::de::DeserializeOwned>(&self, msg: &QueryMsg) -> T {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.contributor.clone(), msg)
+            .unwrap()
+    }
+
+    fn native_balance(&self, account: &Addr) -> u128 {
+        self.app
+            .wrap()
+            .query_balance(account.clone(), DENOM)
+            .unwrap()
+            .amount
+            .u128()
+    }
+
+    fn cw20_balance(&self, token: &Addr, account: &Addr) -> u128 {
+        let response: BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                token.clone(),
+                &Cw20QueryMsg::Balance {
+                    address: account.to_string(),
+                },
+            )
+            .unwrap();
+        response.balance.u128()
+    }
+}
+
+fn instantiate_cw20(
+    app: &mut App,
+    code_id: u64,
+    name: &str,
+    decimals: u8,
+    initial_balances: Vec<Cw20Coin>,
+) -> Addr {
+    app.instantiate_contract(
+        code_id,
+        Addr::unchecked(OWNER),
+        &cw20_base::msg::InstantiateMsg {
+            name: name.to_string(),
+            symbol: "TKN".to_string(),
+            decimals,
+            initial_balances,
+            mint: None,
+            marketing: None,
+        },
+        &[],
+        name,
+        None,
+    )
+    .unwrap()
+}
+
+fn sale_id_bytes(sale_id: u8) -> Vec<u8> {
+    let mut id = vec![0u8; 32];
+    id[31] = sale_id;
+    id
+}
+
+fn native_address(denom: &str) -> Vec<u8> {
+    let mut address = vec![0u8; 32 - denom.len()];
+    address[0] = 1;
+    address.extend(denom.as_bytes());
+    address
+}
+
+fn cw20_address(contract: &Addr) -> Vec<u8> {
+    let canonical = TerraMockApi::default()
+        .addr_canonicalize(contract.as_str())
+        .unwrap();
+    let mut address = vec![0u8; 32 - canonical.len()];
+    address.extend(canonical.as_slice());
+    address
+}
+
+// signs keccak256(message) so it can be recovered with secp256k1_recover_pubkey
+fn sign(key: &str, message: &[u8]) -> Vec<u8> {
+    let key = SigningKey::from_bytes(&hex::decode(key).unwrap()).unwrap();
+    let signature: recoverable::Signature = key.sign_digest(Keccak256::new().chain(message));
+    signature.as_ref().to_vec()
+}
+
+// guardians sign the hash of the body, which is hashed once more on signing
+fn signed_vaa(
+    key: &str,
+    emitter_chain: u16,
+    emitter_address: &[u8],
+    sequence: u64,
+    payload: &[u8],
+) -> Binary {
+    let body = [
+        0u32.to_be_bytes().to_vec(), // timestamp
+        0u32.to_be_bytes().to_vec(), // nonce
+        emitter_chain.to_be_bytes().to_vec(),
+        emitter_address.to_vec(),
+        sequence.to_be_bytes().to_vec(),
+        vec![15u8], // consistency level
+        payload.to_vec(),
+    ]
+    .concat();
+    let signature = sign(key, &Keccak256::digest(&body));
+
+    let header = [
+        vec![1u8],                   // version
+        0u32.to_be_bytes().to_vec(), // guardian set index
+        vec![1u8, 0u8],              // number of signatures, then signer index
+        signature,
+    ]
+    .concat();
+    Binary::from([header, body].concat())
+}
+
+fn kyc_signature(
+    sale_id: u8,
+    token_index: u8,
+    amount: u128,
+    buyer: &str,
+    prior_contribution: u128,
+) -> Binary {
+    let buyer = TerraMockApi::default().addr_canonicalize(buyer).unwrap();
+    let mut token_index_bytes = vec![0u8; 32];
+    token_index_bytes[31] = token_index;
+
+    let encoded = [
+        hex::decode(CONDUCTOR_ADDRESS).unwrap(),
+        sale_id_bytes(sale_id),
+        token_index_bytes,
+        Uint256::from(amount).to_be_bytes().to_vec(),
+        vec![0u8; 32 - buyer.len()],
+        buyer.to_vec(),
+        Uint256::from(prior_contribution).to_be_bytes().to_vec(),
+    ]
+    .concat();
+    Binary::from(sign(KYC_KEY, &encoded))
+}
+
+fn posted_message(suite: &Suite, message: &[u8]) -> Event {
+    Event::new("wasm")
+        .add_attribute("_contract_addr", suite.wormhole.to_string())
+        .add_attribute("message.message", hex::encode(message))
+        .add_attribute("message.sender", suite.contributor.to_string())
+}
+
+fn bridge_transfer(suite: &Suite, token: &str, amount: u128) -> Event {
+    Event::new("wasm")
+        .add_attribute("_contract_addr", suite.token_bridge.to_string())
+        .add_attribute("transfer.token", token)
+        .add_attribute("transfer.amount", amount.to_string())
+        .add_attribute("transfer.recipient_chain", CONDUCTOR_CHAIN.to_string())
+        .add_attribute("transfer.recipient", RECIPIENT)
+}
+
+#[test]
+fn sale_sealed_lifecycle() {
+    let mut suite = Suite::new();
+    let sale_id = Binary::from(sale_id_bytes(1));
+    let buyer_one = Addr::unchecked(BUYER_ONE);
+    let buyer_two = Addr::unchecked(BUYER_TWO);
+
+    suite.init_sale(1).unwrap();
+
+    // nothing is accepted before the sale starts
+    let err = suite.contribute(BUYER_ONE, 1, 0, 1_000_000, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::SaleNotStarted {
+            sale_id: hex::encode(sale_id.as_slice())
+        }
+    );
+
+    // buyer one contributes twice, so the second approval covers the first
+    // contribution
+    suite.set_time(SALE_START);
+    suite.contribute(BUYER_ONE, 1, 0, 1_000_000, 0).unwrap();
+    suite
+        .contribute(BUYER_ONE, 1, 0, 3_000_000, 1_000_000)
+        .unwrap();
+    suite.contribute(BUYER_TWO, 1, 0, 2_000_000, 0).unwrap();
+    suite.contribute(BUYER_TWO, 1, 1, 5_000_000, 0).unwrap();
+
+    // approvals are tied to the buyer's running total
+    let err = suite.contribute(BUYER_TWO, 1, 0, 1_000_000, 0).unwrap_err();
+    assert_eq!(err, ContractError::InvalidKycSignature);
+
+    // foreign tokens are contributed on their own chain
+    let err = suite.contribute(BUYER_ONE, 1, 2, 1_000_000, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WrongChain {
+            expected: 3,
+            actual: CONDUCTOR_CHAIN
+        }
+    );
+
+    assert_eq!(
+        suite.native_balance(&buyer_one),
+        STARTING_BALANCE - 4_000_000
+    );
+    assert_eq!(
+        suite.native_balance(&buyer_two),
+        STARTING_BALANCE - 2_000_000
+    );
+    assert_eq!(suite.native_balance(&suite.contributor), 6_000_000);
+    assert_eq!(
+        suite.cw20_balance(&suite.accepted_cw20, &buyer_two),
+        STARTING_BALANCE - 5_000_000
+    );
+    assert_eq!(
+        suite.cw20_balance(&suite.accepted_cw20, &suite.contributor),
+        5_000_000
+    );
+
+    // contributions can only be attested once the sale is over
+    let attest = ExecuteMsg::AttestContributions {
+        sale_id: sale_id.clone(),
+    };
+    let err = suite.execute(RELAYER, &attest, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::SaleNotFinished {
+            sale_id: hex::encode(sale_id.as_slice())
+        }
+    );
+
+    suite.set_time(SALE_END + 1);
+    let err = suite
+        .contribute(BUYER_ONE, 1, 0, 1_000_000, 4_000_000)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::SaleEnded {
+            sale_id: hex::encode(sale_id.as_slice())
+        }
+    );
+
+    let res = suite.execute(RELAYER, &attest, 0).unwrap();
+    let mut contributions = ContributionsSealed::new(sale_id.as_slice(), 3);
+    contributions
+        .add_contribution(0, Uint256::from(6_000_000u128))
+        .unwrap();
+    contributions
+        .add_contribution(1, Uint256::from(5_000_000u128))
+        .unwrap();
+    res.assert_event(&posted_message(&suite, &contributions.serialize()));
+
+    // allocations are in the sale token's native decimals, which the wrapped
+    // token truncates to 8
+    let mut sealed = SaleSealed {
+        sale_id: sale_id.to_vec(),
+        allocations: vec![],
+    };
+    sealed
+        .add_allocation(
+            0,
+            Uint256::from(6_000_000_000_000_000_000u128),
+            Uint256::from(600_000u128),
+        )
+        .unwrap();
+    sealed
+        .add_allocation(
+            1,
+            Uint256::from(10_000_000_000_000_000_000u128),
+            Uint256::zero(),
+        )
+        .unwrap();
+    sealed
+        .add_allocation(
+            2,
+            Uint256::from(4_000_000_000_000_000_000u128),
+            Uint256::zero(),
+        )
+        .unwrap();
+    let sale_sealed = ExecuteMsg::SaleSealed {
+        data: suite.conductor_vaa(&sealed.serialize()),
+    };
+
+    // the sale token has not arrived yet. the vaa is not consumed when the
+    // transaction fails, so it can be submitted again later
+    let err = suite.execute(RELAYER, &sale_sealed, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientFunds {
+            sale_id: hex::encode(sale_id.as_slice())
+        }
+    );
+
+    let local_allocations = 1_600_000_000u128;
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.sale_token.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: suite.contributor.to_string(),
+                amount: Uint128::new(local_allocations),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let res = suite.execute(RELAYER, &sale_sealed, 0).unwrap();
+    res.assert_event(&bridge_transfer(&suite, DENOM, 5_400_000));
+    res.assert_event(&bridge_transfer(
+        &suite,
+        suite.accepted_cw20.as_str(),
+        5_000_000,
+    ));
+
+    let err = suite.execute(RELAYER, &sale_sealed, 0).unwrap_err();
+    assert_eq!(err, ContractError::VaaAlreadyExecuted);
+
+    // excess contributions stay behind for buyers to claim
+    assert_eq!(suite.native_balance(&suite.contributor), 600_000);
+    assert_eq!(suite.native_balance(&suite.token_bridge), 5_400_000);
+    assert_eq!(
+        suite.cw20_balance(&suite.accepted_cw20, &suite.contributor),
+        0
+    );
+    assert_eq!(
+        suite.cw20_balance(&suite.accepted_cw20, &suite.token_bridge),
+        5_000_000
+    );
+
+    let err = suite.claim_allocation(BUYER_ONE, 1, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::AllocationsLocked {
+            sale_id: hex::encode(sale_id.as_slice())
+        }
+    );
+
+    suite.set_time(UNLOCK_ALLOCATION);
+    suite.claim_allocation(BUYER_ONE, 1, 0).unwrap();
+    suite.claim_allocation(BUYER_TWO, 1, 0).unwrap();
+    suite.claim_allocation(BUYER_TWO, 1, 1).unwrap();
+
+    let err = suite.claim_allocation(BUYER_ONE, 1, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::AllocationAlreadyClaimed {
+            sale_id: hex::encode(sale_id.as_slice()),
+            token_index: 0
+        }
+    );
+    let err = suite.claim_allocation(BUYER_ONE, 1, 1).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BuyerStatusNotFound {
+            sale_id: hex::encode(sale_id.as_slice()),
+            token_index: 1
+        }
+    );
+    let err = suite.claim_refund(BUYER_ONE, 1, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::SaleNotAborted {
+            sale_id: hex::encode(sale_id.as_slice())
+        }
+    );
+
+    assert_eq!(
+        suite.cw20_balance(&suite.sale_token, &buyer_one),
+        400_000_000
+    );
+    assert_eq!(
+        suite.cw20_balance(&suite.sale_token, &buyer_two),
+        1_200_000_000
+    );
+    assert_eq!(suite.cw20_balance(&suite.sale_token, &suite.contributor), 0);
+    assert_eq!(
+        suite.native_balance(&buyer_one),
+        STARTING_BALANCE - 4_000_000 + 400_000
+    );
+    assert_eq!(
+        suite.native_balance(&buyer_two),
+        STARTING_BALANCE - 2_000_000 + 200_000
+    );
+    assert_eq!(suite.native_balance(&suite.contributor), 0);
+
+    // and every query agrees with what happened
+    let sale_init = suite.sale_init(1);
+    let config: ConfigResponse = suite.query(&QueryMsg::Config {});
+    assert_eq!(
+        config,
+        ConfigResponse {
+            conductor_chain: CONDUCTOR_CHAIN,
+            conductor_address: hex::decode(CONDUCTOR_ADDRESS).unwrap(),
+        }
+    );
+
+    let registry: SaleRegistryResponse = suite.query(&QueryMsg::SaleRegistry {
+        sale_id: sale_id.clone(),
+    });
+    let expected_registry = SaleRegistryResponse {
+        id: sale_id.to_vec(),
+        token_address: hex::decode(SALE_TOKEN_ADDRESS).unwrap(),
+        token_chain: CONDUCTOR_CHAIN,
+        token_decimals: SALE_TOKEN_DECIMALS,
+        sale_start: sale_init.core.times.start,
+        sale_end: sale_init.core.times.end,
+        recipient: hex::decode(RECIPIENT).unwrap(),
+        authority: hex::decode(KYC_AUTHORITY).unwrap(),
+    };
+    assert_eq!(registry, expected_registry);
+
+    let all_sales: AllSalesResponse = suite.query(&QueryMsg::AllSales {
+        start_after: None,
+        limit: None,
+    });
+    assert_eq!(all_sales.sales, vec![expected_registry]);
+
+    let status: SaleStatusResponse = suite.query(&QueryMsg::SaleStatus {
+        sale_id: sale_id.clone(),
+    });
+    assert_eq!(
+        status,
+        SaleStatusResponse {
+            id: sale_id.to_vec(),
+            is_sealed: true,
+            is_aborted: false,
+        }
+    );
+
+    let times: SaleTimesResponse = suite.query(&QueryMsg::SaleTimes {
+        sale_id: sale_id.clone(),
+    });
+    assert_eq!(
+        times,
+        SaleTimesResponse {
+            id: sale_id.to_vec(),
+            start: sale_init.core.times.start,
+            end: sale_init.core.times.end,
+            unlock_allocation: sale_init.core.times.unlock_allocation,
+        }
+    );
+
+    let accepted_token: AcceptedTokenResponse = suite.query(&QueryMsg::AcceptedToken {
+        sale_id: sale_id.clone(),
+        token_index: 1,
+    });
+    assert_eq!(
+        accepted_token,
+        AcceptedTokenResponse {
+            id: sale_id.to_vec(),
+            token_index: 1,
+            chain: 3,
+            address: cw20_address(&suite.accepted_cw20),
+            conversion_rate: sale_init.accepted_tokens[1].conversion_rate,
+        }
+    );
+
+    let accepted_tokens: AcceptedTokensResponse = suite.query(&QueryMsg::AcceptedTokens {
+        sale_id: sale_id.clone(),
+    });
+    let totals = [
+        (6_000_000u128, 6_000_000_000_000_000_000u128, 600_000u128),
+        (5_000_000, 10_000_000_000_000_000_000, 0),
+        (0, 4_000_000_000_000_000_000, 0),
+    ];
+    let expected_tokens: Vec<AcceptedTokenTotals> = sale_init
+        .accepted_tokens
+        .iter()
+        .zip(totals.iter())
+        .enumerate()
+        .map(
+            |(token_index, (token, (contribution, allocation, excess)))| AcceptedTokenTotals {
+                token_index: token_index as u8,
+                chain: token.chain,
+                address: token.address.clone(),
+                conversion_rate: token.conversion_rate,
+                total_contribution: Uint256::from(*contribution),
+                total_allocation: Uint256::from(*allocation),
+                total_excess_contribution: Uint256::from(*excess),
+            },
+        )
+        .collect();
+    assert_eq!(
+        accepted_tokens,
+        AcceptedTokensResponse {
+            id: sale_id.to_vec(),
+            tokens: expected_tokens,
+        }
+    );
+
+    let total_contribution: TotalContributionResponse = suite.query(&QueryMsg::TotalContribution {
+        sale_id: sale_id.clone(),
+        token_index: 0,
+    });
+    assert_eq!(
+        total_contribution,
+        TotalContributionResponse {
+            id: sale_id.to_vec(),
+            token_index: 0,
+            amount: Uint256::from(6_000_000u128),
+        }
+    );
+
+    let total_allocation: TotalAllocationResponse = suite.query(&QueryMsg::TotalAllocation {
+        sale_id: sale_id.clone(),
+        token_index: 2,
+    });
+    assert_eq!(
+        total_allocation,
+        TotalAllocationResponse {
+            id: sale_id.to_vec(),
+            token_index: 2,
+            amount: Uint256::from(4_000_000_000_000_000_000u128),
+        }
+    );
+
+    let buyer_status: BuyerStatusResponse = suite.query(&QueryMsg::BuyerStatus {
+        sale_id: sale_id.clone(),
+        token_index: 0,
+        buyer: BUYER_ONE.to_string(),
+    });
+    assert_eq!(
+        buyer_status,
+        BuyerStatusResponse {
+            id: sale_id.to_vec(),
+            token_index: 0,
+            buyer: BUYER_ONE.to_string(),
+            contribution: Uint256::from(4_000_000u128),
+            allocation_is_claimed: true,
+            refund_is_claimed: false,
+        }
+    );
+
+    let buyer_contributions: BuyerContributionsResponse =
+        suite.query(&QueryMsg::BuyerContributions {
+            sale_id: sale_id.clone(),
+            buyer: BUYER_TWO.to_string(),
+        });
+    assert_eq!(
+        buyer_contributions,
+        BuyerContributionsResponse {
+            id: sale_id.to_vec(),
+            buyer: BUYER_TWO.to_string(),
+            contributions: vec![
+                BuyerContribution {
+                    token_index: 0,
+                    contribution: Uint256::from(2_000_000u128),
+                    allocation_is_claimed: true,
+                    refund_is_claimed: false,
+                },
+                BuyerContribution {
+                    token_index: 1,
+                    contribution: Uint256::from(5_000_000u128),
+                    allocation_is_claimed: true,
+                    refund_is_claimed: false,
+                },
+            ],
+        }
+    );
+}
+
+#[test]
+fn sale_aborted_lifecycle() {
+    let mut suite = Suite::new();
+    let sale_id = Binary::from(sale_id_bytes(2));
+    let buyer_one = Addr::unchecked(BUYER_ONE);
+    let buyer_two = Addr::unchecked(BUYER_TWO);
+
+    suite.init_sale(2).unwrap();
+    suite.set_time(SALE_START);
+    suite.contribute(BUYER_ONE, 2, 0, 3_000_000, 0).unwrap();
+    suite.contribute(BUYER_TWO, 2, 1, 1_000_000, 0).unwrap();
+
+    // the conductor can abort the sale before it ends
+    let aborted = SaleAborted {
+        sale_id: sale_id.to_vec(),
+    };
+    let data = suite.conductor_vaa(&aborted.serialize());
+    suite
+        .execute(RELAYER, &ExecuteMsg::SaleAborted { data }, 0)
+        .unwrap();
+
+    let err = suite
+        .contribute(BUYER_ONE, 2, 0, 1_000_000, 3_000_000)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::SaleAborted {
+            sale_id: hex::encode(sale_id.as_slice())
+        }
+    );
+
+    suite.set_time(SALE_END + 1);
+    let attest = ExecuteMsg::AttestContributions {
+        sale_id: sale_id.clone(),
+    };
+    let err = suite.execute(RELAYER, &attest, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::SaleAlreadySealedOrAborted {
+            sale_id: hex::encode(sale_id.as_slice())
+        }
+    );
+
+    suite.set_time(UNLOCK_ALLOCATION);
+    let err = suite.claim_allocation(BUYER_ONE, 2, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::SaleNotSealed {
+            sale_id: hex::encode(sale_id.as_slice())
+        }
+    );
+
+    suite.claim_refund(BUYER_ONE, 2, 0).unwrap();
+    suite.claim_refund(BUYER_TWO, 2, 1).unwrap();
+
+    let err = suite.claim_refund(BUYER_TWO, 2, 1).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RefundAlreadyClaimed {
+            sale_id: hex::encode(sale_id.as_slice()),
+            token_index: 1
+        }
+    );
+
+    // everybody is made whole and nothing was bridged
+    assert_eq!(suite.native_balance(&buyer_one), STARTING_BALANCE);
+    assert_eq!(
+        suite.cw20_balance(&suite.accepted_cw20, &buyer_two),
+        STARTING_BALANCE
+    );
+    assert_eq!(suite.native_balance(&suite.contributor), 0);
+    assert_eq!(
+        suite.cw20_balance(&suite.accepted_cw20, &suite.contributor),
+        0
+    );
+    assert_eq!(suite.native_balance(&suite.token_bridge), 0);
+
+    let status: SaleStatusResponse = suite.query(&QueryMsg::SaleStatus {
+        sale_id: sale_id.clone(),
+    });
+    assert_eq!(
+        status,
+        SaleStatusResponse {
+            id: sale_id.to_vec(),
+            is_sealed: false,
+            is_aborted: true,
+        }
+    );
+
+    let buyer_contributions: BuyerContributionsResponse =
+        suite.query(&QueryMsg::BuyerContributions {
+            sale_id: sale_id.clone(),
+            buyer: BUYER_ONE.to_string(),
+        });
+    assert_eq!(
+        buyer_contributions.contributions,
+        vec![BuyerContribution {
+            token_index: 0,
+            contribution: Uint256::from(3_000_000u128),
+            allocation_is_claimed: false,
+            refund_is_claimed: true,
+        }]
+    );
+}
+
+#[test]
+fn vaas_must_be_signed_by_the_guardian() {
+    let mut suite = Suite::new();
+
+    let payload = suite.sale_init(3).serialize();
+    let data = signed_vaa(
+        KYC_KEY,
+        CONDUCTOR_CHAIN,
+        &hex::decode(CONDUCTOR_ADDRESS).unwrap(),
+        0,
+        &payload,
+    );
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(RELAYER),
+            suite.contributor.clone(),
+            &ExecuteMsg::InitSale { data },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid guardian signature"));
+
+    // the same sale goes through once the guardian signs it, but only once
+    let data = suite.conductor_vaa(&payload);
+    suite
+        .execute(RELAYER, &ExecuteMsg::InitSale { data: data.clone() }, 0)
+        .unwrap();
+    let err = suite
+        .execute(RELAYER, &ExecuteMsg::InitSale { data }, 0)
+        .unwrap_err();
+    assert_eq!(err, ContractError::VaaAlreadyExecuted);
+}
@@ -0,0 +1,201 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult,
+    WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_multi_test::{Contract, ContractWrapper};
+use cw_storage_plus::Item;
+use sha3::{Digest, Keccak256};
+use terraswap::asset::AssetInfo;
+
+use token_bridge_terra::msg::{
+    ExecuteMsg as TokenBridgeExecuteMsg, QueryMsg as TokenBridgeQueryMsg, WrappedRegistryResponse,
+};
+use wormhole::{
+    msg::{ExecuteMsg as WormholeExecuteMsg, QueryMsg as WormholeQueryMsg},
+    state::ParsedVAA,
+};
+
+use crate::contract::{execute, instantiate, migrate, query, reply};
+
+pub fn contributor_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(execute, instantiate, query)
+        .with_reply(reply)
+        .with_migrate(migrate);
+    Box::new(contract)
+}
+
+pub fn cw20_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn wormhole_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(wormhole_execute, wormhole_instantiate, wormhole_query);
+    Box::new(contract)
+}
+
+pub fn token_bridge_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        token_bridge_execute,
+        token_bridge_instantiate,
+        token_bridge_query,
+    );
+    Box::new(contract)
+}
+
+/// the mock wormhole has a single guardian, identified by its ethereum address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MockWormholeInstantiateMsg {
+    pub guardian: Binary,
+}
+
+/// the mock token bridge resolves every foreign asset to the same wrapped cw20
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MockTokenBridgeInstantiateMsg {
+    pub wrapped_asset: String,
+}
+
+const GUARDIAN: Item<Vec<u8>> = Item::new("guardian");
+const SEQUENCE: Item<u64> = Item::new("sequence");
+const WRAPPED_ASSET: Item<String> = Item::new("wrapped_asset");
+
+fn wormhole_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: MockWormholeInstantiateMsg,
+) -> StdResult<Response> {
+    GUARDIAN.save(deps.storage, &msg.guardian.to_vec())?;
+    SEQUENCE.save(deps.storage, &0)?;
+    Ok(Response::default())
+}
+
+// posted messages are only emitted as attributes, the same way the real
+// contract reports them to the guardians
+fn wormhole_execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: WormholeExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        WormholeExecuteMsg::PostMessage { message, nonce } => {
+            let sequence = SEQUENCE.load(deps.storage)?;
+            SEQUENCE.save(deps.storage, &(sequence + 1))?;
+
+            Ok(Response::new()
+                .add_attribute("message.message", hex::encode(message.as_slice()))
+                .add_attribute("message.sender", info.sender.to_string())
+                .add_attribute("message.nonce", nonce.to_string())
+                .add_attribute("message.sequence", sequence.to_string()))
+        }
+        _ => Err(StdError::generic_err("not supported by the mock wormhole")),
+    }
+}
+
+fn wormhole_query(deps: Deps, _env: Env, msg: WormholeQueryMsg) -> StdResult<Binary> {
+    match msg {
+        WormholeQueryMsg::VerifyVAA { vaa, block_time: _ } => {
+            to_binary(&verify_vaa(deps, vaa.as_slice())?)
+        }
+        _ => Err(StdError::generic_err("not supported by the mock wormhole")),
+    }
+}
+
+// expects exactly one signature, made by the guardian over the vaa hash
+fn verify_vaa(deps: Deps, data: &[u8]) -> StdResult<ParsedVAA> {
+    let vaa = ParsedVAA::deserialize(data)?;
+    if vaa.len_signers != 1 {
+        return Err(StdError::generic_err(
+            "expected a single guardian signature",
+        ));
+    }
+
+    // header is version (1), guardian set index (4) and signer count (1),
+    // followed by the signer index (1) and the signature (65)
+    let signature = &data[7..72];
+    let recovered = deps
+        .api
+        .secp256k1_recover_pubkey(&vaa.hash, &signature[0..64], signature[64])
+        .map_err(|_| StdError::generic_err("cannot recover guardian key"))?;
+    let recovered = Keccak256::digest(&recovered[1..]);
+    if recovered[12..] != GUARDIAN.load(deps.storage)?[..] {
+        return Err(StdError::generic_err("invalid guardian signature"));
+    }
+
+    Ok(vaa)
+}
+
+fn token_bridge_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: MockTokenBridgeInstantiateMsg,
+) -> StdResult<Response> {
+    WRAPPED_ASSET.save(deps.storage, &msg.wrapped_asset)?;
+    Ok(Response::default())
+}
+
+// native deposits simply stay with the mock. cw20s are pulled from the sender,
+// which needs to have approved the transfer amount beforehand
+fn token_bridge_execute(
+    _deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: TokenBridgeExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        TokenBridgeExecuteMsg::DepositTokens {} => {
+            Ok(Response::new().add_attribute("action", "deposit_tokens"))
+        }
+        TokenBridgeExecuteMsg::InitiateTransfer {
+            asset,
+            recipient_chain,
+            recipient,
+            fee: _,
+            nonce: _,
+        } => {
+            let mut response = Response::new();
+            let token = match asset.info {
+                AssetInfo::NativeToken { denom } => denom,
+                AssetInfo::Token { contract_addr } => {
+                    response = response.add_message(WasmMsg::Execute {
+                        contract_addr: contract_addr.clone(),
+                        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                            owner: info.sender.to_string(),
+                            recipient: env.contract.address.to_string(),
+                            amount: asset.amount,
+                        })?,
+                        funds: vec![],
+                    });
+                    contract_addr
+                }
+            };
+
+            Ok(response
+                .add_attribute("transfer.token", token)
+                .add_attribute("transfer.amount", asset.amount.to_string())
+                .add_attribute("transfer.recipient_chain", recipient_chain.to_string())
+                .add_attribute("transfer.recipient", hex::encode(recipient.as_slice())))
+        }
+        _ => Err(StdError::generic_err(
+            "not supported by the mock token bridge",
+        )),
+    }
+}
+
+fn token_bridge_query(deps: Deps, _env: Env, msg: TokenBridgeQueryMsg) -> StdResult<Binary> {
+    match msg {
+        TokenBridgeQueryMsg::WrappedRegistry { .. } => to_binary(&WrappedRegistryResponse {
+            address: WRAPPED_ASSET.load(deps.storage)?,
+        }),
+    }
+}
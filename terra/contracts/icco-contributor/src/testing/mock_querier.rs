@@ -3,30 +3,136 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, from_slice, to_binary, Coin, ContractResult, Empty, OwnedDeps, Querier,
-    QuerierResult, QueryRequest, SystemError, SystemResult, WasmQuery,
+    from_binary, from_slice, to_binary, Addr, Api, CanonicalAddr, Coin, ContractResult, Empty,
+    OwnedDeps, Querier, QuerierResult, QueryRequest, RecoverPubkeyError, StdError, StdResult,
+    SystemError, SystemResult, Uint128, VerificationError, WasmQuery,
 };
+use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 
+use token_bridge_terra::msg::{QueryMsg as TokenBridgeQueryMsg, WrappedRegistryResponse};
 use wormhole::{msg::QueryMsg as WormholeQueryMsg, state::ParsedVAA};
 
+pub const MOCK_WORMHOLE_ADDR: &str = "wormhole";
+pub const MOCK_TOKEN_BRIDGE_ADDR: &str = "tokenbridge";
+
+/// every foreign token resolves to this wrapped cw20
+pub const MOCK_WRAPPED_ADDR: &str = "wrapped";
+
+/// there is no contract behind this address, so every query to it fails
+pub const MOCK_INVALID_CW20_ADDR: &str = "notacw20";
+
+/// decimals reported by every mocked cw20, which matches what the token
+/// bridge uses for wrapped tokens
+pub const MOCK_CW20_DECIMALS: u8 = 8;
+
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
 pub fn mock_dependencies(
     contract_balance: &[Coin],
-) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+) -> OwnedDeps<MockStorage, TerraMockApi, WasmMockQuerier> {
     let custom_querier: WasmMockQuerier =
         WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
 
     OwnedDeps {
-        api: MockApi::default(),
+        api: TerraMockApi::default(),
         storage: MockStorage::default(),
         querier: custom_querier,
     }
 }
 
+pub const CANONICAL_LENGTH: usize = 20;
+
+/// TerraMockApi canonicalizes human addresses to 20 bytes (like Terra does) by
+/// right-padding them with zeros, so they can be left-padded to 32 bytes the same
+/// way the contract encodes addresses for cross-chain messages. Crypto is handled
+/// by the regular MockApi.
+#[derive(Copy, Clone, Default)]
+pub struct TerraMockApi {
+    base: MockApi,
+}
+
+impl Api for TerraMockApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.addr_canonicalize(human)?;
+        Ok(Addr::unchecked(human))
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        if human.is_empty() || human.len() > CANONICAL_LENGTH {
+            return Err(StdError::generic_err(format!(
+                "Invalid input: human address must be 1 to {} bytes",
+                CANONICAL_LENGTH
+            )));
+        }
+        let mut canonical = human.as_bytes().to_vec();
+        canonical.resize(CANONICAL_LENGTH, 0u8);
+        Ok(canonical.into())
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        if canonical.len() != CANONICAL_LENGTH {
+            return Err(StdError::generic_err(
+                "Invalid input: canonical address length",
+            ));
+        }
+        let trimmed: Vec<u8> = canonical
+            .as_slice()
+            .iter()
+            .cloned()
+            .take_while(|&byte| byte != 0)
+            .collect();
+        let human = String::from_utf8(trimmed)?;
+        Ok(Addr::unchecked(human))
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.base
+            .secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.base
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.base.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.base
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.base.debug(message)
+    }
+}
+
 pub struct WasmMockQuerier {
     base: MockQuerier<Empty>,
     minter_querier: MinterQuerier,
+    cw20_balance: Uint128,
 }
 
 #[derive(Clone, Default)]
@@ -67,17 +173,41 @@ impl Querier for WasmMockQuerier {
 impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
         match &request {
-            QueryRequest::Wasm(WasmQuery::Smart {
-                contract_addr: _,
-                msg,
-            }) => match from_binary(msg) {
-                Ok(WormholeQueryMsg::VerifyVAA { vaa, block_time: _ }) => {
-                    SystemResult::Ok(ContractResult::from(to_binary(
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                if contract_addr == MOCK_INVALID_CW20_ADDR {
+                    return SystemResult::Err(SystemError::NoSuchContract {
+                        addr: contract_addr.clone(),
+                    });
+                }
+                if let Ok(WormholeQueryMsg::VerifyVAA { vaa, block_time: _ }) = from_binary(msg) {
+                    return SystemResult::Ok(ContractResult::from(to_binary(
                         &ParsedVAA::deserialize(vaa.as_slice()).unwrap(),
-                    )))
+                    )));
+                }
+                if let Ok(TokenBridgeQueryMsg::WrappedRegistry { .. }) = from_binary(msg) {
+                    return SystemResult::Ok(ContractResult::from(to_binary(
+                        &WrappedRegistryResponse {
+                            address: MOCK_WRAPPED_ADDR.to_string(),
+                        },
+                    )));
                 }
-                _ => panic!("query not mocked"),
-            },
+                match from_binary(msg) {
+                    Ok(Cw20QueryMsg::TokenInfo {}) => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&TokenInfoResponse {
+                            name: "mock".to_string(),
+                            symbol: "MOCK".to_string(),
+                            decimals: MOCK_CW20_DECIMALS,
+                            total_supply: Uint128::zero(),
+                        })))
+                    }
+                    Ok(Cw20QueryMsg::Balance { address: _ }) => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&BalanceResponse {
+                            balance: self.cw20_balance,
+                        })))
+                    }
+                    _ => panic!("query not mocked"),
+                }
+            }
             _ => self.base.handle_query(request),
         }
     }
@@ -88,9 +218,15 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             minter_querier: MinterQuerier::default(),
+            cw20_balance: Uint128::zero(),
         }
     }
 
+    // every mocked cw20 reports this balance for any account
+    pub fn with_cw20_balance(&mut self, balance: Uint128) {
+        self.cw20_balance = balance;
+    }
+
     pub fn with_anc_minter(&mut self, minter: String) {
         self.minter_querier = MinterQuerier::new(minter);
     }
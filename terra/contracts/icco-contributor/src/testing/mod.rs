@@ -1,2 +1,4 @@
+mod integration_tests;
+mod mock_contracts;
 mod mock_querier;
 mod tests;
@@ -1,16 +1,29 @@
 use crate::{
-    contract::{execute, instantiate, query},
+    contract::{execute, instantiate, query, reply},
+    error::ContractError,
+    execute::CONTRIBUTE_REPLY_ID,
     msg::{
-        AcceptedTokenResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
-        SaleRegistryResponse, SaleStatusResponse, SaleTimesResponse, TotalAllocationResponse,
-        TotalContributionResponse,
+        AcceptedTokenResponse, AcceptedTokensResponse, AllSalesResponse, BuyerContribution,
+        BuyerContributionsResponse, BuyerStatusResponse, ConfigResponse, ExecuteMsg,
+        InstantiateMsg, MigrateMsg, QueryMsg, SaleRegistryResponse, SaleStatusResponse,
+        SaleTimesResponse, TotalAllocationResponse, TotalContributionResponse,
     },
-    shared::{SaleAborted, SaleSealed},
+    shared::{AuthorityUpdated, ContributionsSealed, SaleAborted, SaleInit, SaleSealed},
     state::SaleMessage,
-    testing::mock_querier::mock_dependencies,
+    testing::mock_querier::{
+        mock_dependencies, TerraMockApi, WasmMockQuerier, MOCK_INVALID_CW20_ADDR,
+        MOCK_TOKEN_BRIDGE_ADDR, MOCK_WORMHOLE_ADDR, MOCK_WRAPPED_ADDR,
+    },
+};
+use cosmwasm_std::testing::{mock_env, mock_info, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    coins, from_binary, to_binary, BankMsg, Binary, ContractResult, CosmosMsg, Deps, DepsMut,
+    OwnedDeps, Reply, Response, StdResult, SubMsg, SubMsgExecutionResponse, Timestamp, Uint128,
+    Uint256, WasmMsg,
 };
-use cosmwasm_std::testing::{mock_env, mock_info};
-use cosmwasm_std::{from_binary, to_binary, Binary, StdResult, Uint256};
+use cw20::Cw20ExecuteMsg;
+use terraswap::asset::{Asset, AssetInfo};
+use token_bridge_terra::msg::ExecuteMsg as TokenBridgeExecuteMsg;
 
 use std::string::String;
 
@@ -145,7 +158,7 @@ use wormhole::byte_utils::ByteUtils;
 const ADDRESS_ZERO: [u8; 32] = [0u8; 32];
 
 #[test]
-fn proper_initialization() -> StdResult<()> {
+fn proper_initialization() -> Result<(), ContractError> {
     let mut deps = mock_dependencies(&[]);
 
     let conductor_chain = 2u16;
@@ -187,7 +200,7 @@ fn proper_initialization() -> StdResult<()> {
 }
 
 #[test]
-fn init_sale() -> StdResult<()> {
+fn init_sale() -> Result<(), ContractError> {
     let mut deps = mock_dependencies(&[]);
 
     let env = mock_env();
@@ -220,30 +233,36 @@ fn init_sale() -> StdResult<()> {
         22710524a6476300000003880000000000020000000000000000000000005f8e\
         26facc23fa4cbd87b8d9dbbd33d5047abde100000000000000000f0100000000\
         0000000000000000000000000000000000000000000000000000000000000000\
-        000000000000000083752ecafebf4707258dedffbd9c7443148169db00020000\
-        000000000000000000000000000000000000000000000de0b6b3a76400000000\
-        000000000000000000000000000000000000000000008ac7230489e800000000\
-        00000000000000000000000000000000000000000000000000000000038c0000\
-        0000000000000000000000000000000000000000000000000000000003c80600\
-        0000000000000000000000ddb64fe46a91d46ee29420539fc25fd07c5fea3e00\
-        0200000000000000000de0b6b3a7640000000000000000000000000000ddb64f\
-        e46a91d46ee29420539fc25fd07c5fea3e0004000000000000000002c68af0bb\
-        1400000000000000000000000000008a5bbc20ad253e296f61601e868a3206b2\
-        d4774c0002000000000000000002c68af0bb1400000000000000000000000000\
-        003d9e7a12daa29a8b2b1bfaa9dc97ce018853ab31000400000000000000000d\
-        e0b6b3a7640000000000000000000000000000ddb64fe46a91d46ee29420539f\
-        c25fd07c5fea3e0004000000000000000002c68af0bb14000000000000000000\
-        0000000000ddb64fe46a91d46ee29420539fc25fd07c5fea3e00040000000000\
-        00000002c68af0bb14000000000000000000000000000022d491bde2303f2f43\
-        325b2108d26f1eaba1e32b00000000000000000000000022d491bde2303f2f43\
-        325b2108d26f1eaba1e32b";
+        000000000000000083752ecafebf4707258dedffbd9c7443148169db00021200\
+        0000000000000000000000000000000000000000000000000000000000038c00\
+        000000000000000000000000000000000000000000000000000000000003c806\
+        000000000000000000000000ddb64fe46a91d46ee29420539fc25fd07c5fea3e\
+        000200000000000000000de0b6b3a7640000000000000000000000000000ddb6\
+        4fe46a91d46ee29420539fc25fd07c5fea3e0004000000000000000002c68af0\
+        bb1400000000000000000000000000008a5bbc20ad253e296f61601e868a3206\
+        b2d4774c0002000000000000000002c68af0bb14000000000000000000000000\
+        00003d9e7a12daa29a8b2b1bfaa9dc97ce018853ab3100040000000000000000\
+        0de0b6b3a7640000000000000000000000000000ddb64fe46a91d46ee2942053\
+        9fc25fd07c5fea3e0004000000000000000002c68af0bb140000000000000000\
+        000000000000ddb64fe46a91d46ee29420539fc25fd07c5fea3e000400000000\
+        0000000002c68af0bb14000000000000000000000000000022d491bde2303f2f\
+        43325b2108d26f1eaba1e32b1df62f291b2e969fb0849d99d9ce41e2f137006e\
+        00000000000000000000000000000000000000000000000000000000000007d0";
     let signed_vaa = hex::decode(signed_vaa).unwrap();
 
     let info = mock_info("addr0001", &[]);
     let msg = ExecuteMsg::InitSale {
         data: Binary::from(signed_vaa.as_slice()),
     };
-    let _response = execute(deps.as_mut(), env.clone(), info, msg)?;
+    let _response = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone())?;
+
+    // the same vaa cannot be executed twice
+    let result = execute(deps.as_mut(), env.clone(), info, msg);
+    assert_eq!(
+        result,
+        Err(ContractError::VaaAlreadyExecuted),
+        "init_sale succeeded twice"
+    );
 
     let sale_id = "0000000000000000000000000000000000000000000000000000000000000000";
     let sale_id = hex::decode(sale_id).unwrap();
@@ -282,8 +301,7 @@ fn init_sale() -> StdResult<()> {
       saleId: '0',
       tokenAddress: '00000000000000000000000083752ecafebf4707258dedffbd9c7443148169db',
       tokenChain: 2,
-      tokenAmount: '1000000000000000000',
-      minRaise: '10000000000000000000',
+      tokenDecimals: 18,
       saleStart: '908',
       saleEnd: '968',
       acceptedTokens: [
@@ -319,7 +337,8 @@ fn init_sale() -> StdResult<()> {
         }
       ],
       recipient: '00000000000000000000000022d491bde2303f2f43325b2108d26f1eaba1e32b',
-      refundRecipient: '00000000000000000000000022d491bde2303f2f43325b2108d26f1eaba1e32b'
+      authority: '1df62f291b2e969fb0849d99d9ce41e2f137006e',
+      unlockTimestamp: '2000'
       */
 
     // token address
@@ -334,15 +353,8 @@ fn init_sale() -> StdResult<()> {
     // token chain
     assert_eq!(sale.token_chain, 2u16);
 
-    // token amount
-    let token_amount = Uint256::from(1_000_000_000_000_000_000u128);
-    assert_eq!(
-        sale.token_amount, token_amount,
-        "sale.token_amount != expected"
-    );
-
-    let min_raise = Uint256::from(10_000_000_000_000_000_000u128);
-    assert_eq!(sale.min_raise, min_raise, "sale.min_raise != expected");
+    // token decimals
+    assert_eq!(sale.token_decimals, 18u8, "sale.token_decimals != expected");
 
     let sale_start = 908u64;
     assert_eq!(sale.sale_start, sale_start, "sale.sale_start != expected");
@@ -354,12 +366,9 @@ fn init_sale() -> StdResult<()> {
     let recipient = hex::decode(recipient).unwrap();
     assert_eq!(&sale.recipient, &recipient, "sale.recipient != expected");
 
-    let refund_recipient = "00000000000000000000000022d491bde2303f2f43325b2108d26f1eaba1e32b";
-    let refund_recipient = hex::decode(refund_recipient).unwrap();
-    assert_eq!(
-        &sale.refund_recipient, &refund_recipient,
-        "sale.refund_recipient != expected"
-    );
+    let authority = "1df62f291b2e969fb0849d99d9ce41e2f137006e";
+    let authority = hex::decode(authority).unwrap();
+    assert_eq!(&sale.authority, &authority, "sale.authority != expected");
 
     // double-check sale times
     let response = query(
@@ -373,6 +382,7 @@ fn init_sale() -> StdResult<()> {
     assert_eq!(sale.id.as_slice(), sale_id);
     assert_eq!(sale.sale_start, sale_times.start);
     assert_eq!(sale.sale_end, sale_times.end);
+    assert_eq!(sale_times.unlock_allocation, 2000u64);
 
     // check accepted tokens
     let accepted_token_addresses = Vec::from([
@@ -463,16 +473,22 @@ fn test_sale_sealed() -> StdResult<()> {
         06\
         00\
         000000000000000000000000000000000000000000000000047a09633e414a52\
+        0000000000000000000000000000000000000000000000000000000000000000\
         01\
         00000000000000000000000000000000000000000000000002fc06415c698000\
+        0000000000000000000000000000000000000000000000000000000000000000\
         02\
         00000000000000000000000000000000000000000000000000729a89eca02108\
+        0000000000000000000000000000000000000000000000000000000000000000\
         03\
         00000000000000000000000000000000000000000000000003bb07d1b383e000\
+        00000000000000000000000000000000000000000000000000000000000f4240\
         04\
         00000000000000000000000000000000000000000000000000e53511bee2f000\
+        0000000000000000000000000000000000000000000000000000000000000000\
         05\
-        0000000000000000000000000000000000000000000000000157cf9cf2604c00";
+        0000000000000000000000000000000000000000000000000157cf9cf2604c00\
+        000000000000000000000000000000000000000000000000000000000000c350";
 
     let vaa_payload = hex::decode(vaa_payload_stringified).unwrap();
 
@@ -486,6 +502,36 @@ fn test_sale_sealed() -> StdResult<()> {
     assert_eq!(sale_sealed.sale_id.len(), expected_sale_id.len());
     assert_eq!(sale_sealed.sale_id, expected_sale_id);
 
+    // allocations and excess contributions
+    let expected_allocations = Vec::from([
+        322_580_645_161_290_322u128,
+        215_053_760_000_000_000u128,
+        32_258_064_516_129_032u128,
+        268_817_200_000_000_000u128,
+        64_516_120_000_000_000u128,
+        96_774_190_000_000_000u128,
+    ]);
+    let expected_excess_contributions = Vec::from([0u128, 0, 0, 1_000_000, 0, 50_000]);
+    assert_eq!(sale_sealed.allocations.len(), expected_allocations.len());
+    for (i, allocation) in sale_sealed.allocations.iter().enumerate() {
+        assert_eq!(allocation.token_index, i as u8);
+        assert_eq!(
+            allocation.allocated,
+            Uint256::from(expected_allocations[i]),
+            "allocation.allocated != expected"
+        );
+        assert_eq!(
+            allocation.excess_contributed,
+            Uint256::from(expected_excess_contributions[i]),
+            "allocation.excess_contributed != expected"
+        );
+    }
+
+    assert_eq!(sale_sealed.serialize(), vaa_payload, "round trip failed");
+
+    // a sale id without an allocation count is not a valid payload
+    assert!(SaleSealed::deserialize(&message.payload[..32]).is_err());
+
     Ok(())
 }
 
@@ -507,5 +553,1440 @@ fn test_sale_aborted() -> StdResult<()> {
     assert_eq!(sale_aborted.sale_id.len(), expected_sale_id.len());
     assert_eq!(sale_aborted.sale_id, expected_sale_id);
 
+    assert_eq!(sale_aborted.serialize(), vaa_payload, "round trip failed");
+
+    // the payload is exactly one sale id
+    assert!(SaleAborted::deserialize(&message.payload[..31]).is_err());
+    let mut too_long = vaa_payload.clone();
+    too_long.push(0);
+    let message = SaleMessage::deserialize(too_long.as_slice())?;
+    assert!(SaleAborted::deserialize(message.payload).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_sale_init() -> StdResult<()> {
+    let vaa_payload_stringified = "\
+        0100000000000000000000000000000000000000000000000000000000000000\
+        0300000000000000000000000083752ecafebf4707258dedffbd9c7443148169\
+        db00021200000000000000000000000000000000000000000000000000000000\
+        5dafb80800000000000000000000000000000000000000000000000000000000\
+        5dafbbf003000000000000000000000000ddb64fe46a91d46ee29420539fc25f\
+        d07c5fea3e000200000000000000000de0b6b3a7640000000000000000000000\
+        00000063773230746f6b656e0000000000000000000000000300000000000000\
+        000de0b6b3a76400000100000000000000000000000000000000000000000000\
+        00000000756c756e610003000000000000000002c68af0bb1400000000000000\
+        0000000000000022d491bde2303f2f43325b2108d26f1eaba1e32b1df62f291b\
+        2e969fb0849d99d9ce41e2f137006e0000000000000000000000000000000000\
+        00000000000000000000005dafbfd8";
+
+    let vaa_payload = hex::decode(vaa_payload_stringified).unwrap();
+
+    let message = SaleMessage::deserialize(vaa_payload.as_slice())?;
+    assert_eq!(message.id, SaleInit::PAYLOAD_ID);
+
+    let sale_init = SaleInit::deserialize(message.payload)?;
+
+    let mut expected_sale_id = vec![0u8; 32];
+    expected_sale_id[31] = 3;
+    assert_eq!(sale_init.core.id, expected_sale_id);
+    assert_eq!(sale_init.core.token_chain, 2u16);
+    assert_eq!(sale_init.core.token_decimals, 18u8);
+    assert_eq!(sale_init.core.times.start, 1_571_797_000u64);
+    assert_eq!(sale_init.core.times.end, 1_571_798_000u64);
+    assert_eq!(sale_init.core.times.unlock_allocation, 1_571_799_000u64);
+    assert_eq!(
+        sale_init.core.authority,
+        hex::decode("1df62f291b2e969fb0849d99d9ce41e2f137006e").unwrap()
+    );
+
+    let expected_chains = Vec::from([2u16, 3u16, 3u16]);
+    assert_eq!(sale_init.accepted_tokens.len(), expected_chains.len());
+    for (token, chain) in sale_init.accepted_tokens.iter().zip(expected_chains) {
+        assert_eq!(token.chain, chain, "token.chain != expected");
+    }
+
+    assert_eq!(sale_init.serialize(), vaa_payload, "round trip failed");
+
+    // trailing bytes are not part of the conductor's encoding
+    let mut too_long = vaa_payload.clone();
+    too_long.push(0);
+    let message = SaleMessage::deserialize(too_long.as_slice())?;
+    assert!(SaleInit::deserialize(message.payload).is_err());
+
+    // truncated payloads are rejected rather than read out of bounds
+    let payload = &vaa_payload[1..];
+    for len in [0, 66, 131, 200, payload.len() - 1] {
+        assert!(SaleInit::deserialize(&payload[..len]).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_contributions_sealed() -> StdResult<()> {
+    // as encoded by a solana contributor
+    let vaa_payload_stringified = "\
+        0200000000000000000000000000000000000000000000000000000000000000\
+        020001c2b0d0c7e6f0b8b4e1d2a8d3f0c9e8a7b6c5d4e3f2a1b0c9d8e7f6a5b4\
+        c3d2e10200000000000000000000000000000000000000000000000000ab54a9\
+        8ceb1f0ad2030000000000000000000000000000000000000000000000000000\
+        00000000002a";
+
+    let vaa_payload = hex::decode(vaa_payload_stringified).unwrap();
+
+    let message = SaleMessage::deserialize(vaa_payload.as_slice())?;
+    assert_eq!(message.id, ContributionsSealed::PAYLOAD_ID);
+
+    let contributions_sealed = ContributionsSealed::deserialize(message.payload)?;
+    assert_eq!(contributions_sealed.chain_id, 1u16);
+    assert_eq!(
+        contributions_sealed.solana_token_account,
+        hex::decode("c2b0d0c7e6f0b8b4e1d2a8d3f0c9e8a7b6c5d4e3f2a1b0c9d8e7f6a5b4c3d2e1").unwrap()
+    );
+    assert_eq!(contributions_sealed.contributions.len(), 2);
+    assert_eq!(contributions_sealed.contributions[0].token_index, 0u8);
+    assert_eq!(
+        contributions_sealed.contributions[0].contributed,
+        Uint256::from(12_345_678_901_234_567_890u128)
+    );
+    assert_eq!(contributions_sealed.contributions[1].token_index, 3u8);
+    assert_eq!(
+        contributions_sealed.contributions[1].contributed,
+        Uint256::from(42u128)
+    );
+
+    assert_eq!(
+        contributions_sealed.serialize(),
+        vaa_payload,
+        "round trip failed"
+    );
+
+    // and what Terra attests to has a zeroed out token account
+    let mut expected_sale_id = vec![0u8; 32];
+    expected_sale_id[31] = 2;
+    let mut terra_sealed = ContributionsSealed::new(&expected_sale_id, 3u16);
+    terra_sealed.add_contribution(1u8, Uint256::from(1_000_000u128))?;
+
+    let encoded = terra_sealed.serialize();
+    assert_eq!(encoded.len(), 1 + 32 + 2 + 32 + 1 + 33);
+    assert_eq!(&encoded[35..67], &[0u8; 32][..]);
+
+    let message = SaleMessage::deserialize(encoded.as_slice())?;
+    assert_eq!(
+        ContributionsSealed::deserialize(message.payload)?,
+        terra_sealed
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_authority_updated() -> StdResult<()> {
+    let vaa_payload_stringified = "\
+        0600000000000000000000000000000000000000000000000000000000000000\
+        02befa429d57cd18b7f8a4d91a2da9ab4af05d0fbe";
+
+    let vaa_payload = hex::decode(vaa_payload_stringified).unwrap();
+
+    let message = SaleMessage::deserialize(vaa_payload.as_slice())?;
+    assert_eq!(message.id, AuthorityUpdated::PAYLOAD_ID);
+
+    let authority_updated = AuthorityUpdated::deserialize(message.payload)?;
+
+    let mut expected_sale_id = vec![0u8; 32];
+    expected_sale_id[31] = 2;
+    assert_eq!(authority_updated.sale_id, expected_sale_id);
+    assert_eq!(
+        authority_updated.new_authority,
+        hex::decode("befa429d57cd18b7f8a4d91a2da9ab4af05d0fbe").unwrap()
+    );
+
+    assert_eq!(
+        authority_updated.serialize(),
+        vaa_payload,
+        "round trip failed"
+    );
+
+    Ok(())
+}
+
+const CONTRIBUTE_SALE_ID: [u8; 32] = {
+    let mut sale_id = [0u8; 32];
+    sale_id[31] = 1;
+    sale_id
+};
+
+// sale 1 accepts a Terra CW20 (index 0), uluna (index 1) and a foreign token
+// (index 2). the sale is open for mock_env's block time and its KYC authority
+// is 1df62f291b2e969fb0849d99d9ce41e2f137006e
+fn instantiate_and_init_contribute_sale(mut deps: DepsMut) -> Result<(), ContractError> {
+    let conductor_address = "0000000000000000000000005f8e26facc23fa4cbd87b8d9dbbd33d5047abde1";
+    let conductor_address = hex::decode(conductor_address).unwrap();
+
+    let msg = InstantiateMsg {
+        gov_chain: 1,
+        gov_address: Binary::from(Vec::from(ADDRESS_ZERO)),
+        wormhole_contract: MOCK_WORMHOLE_ADDR.to_string(),
+        token_bridge_contract: MOCK_TOKEN_BRIDGE_ADDR.to_string(),
+        conductor_chain: 2u16,
+        conductor_address: Binary::from(conductor_address),
+    };
+    instantiate(deps.branch(), mock_env(), mock_info("creator", &[]), msg)?;
+
+    let signed_vaa = "\
+        01000000000100fd75a4cd9fe22519fbe7856267f59d0e0bf9931bf8a7733de4\
+        e7b576451ae1b3309c9adee5c96e82e9b06b03fa16e189e3fe9eff132c1f9ce9\
+        22710524a6476300000003880000000000020000000000000000000000005f8e\
+        26facc23fa4cbd87b8d9dbbd33d5047abde100000000000000010f0100000000\
+        0000000000000000000000000000000000000000000000000000000100000000\
+        000000000000000083752ecafebf4707258dedffbd9c7443148169db00021200\
+        0000000000000000000000000000000000000000000000000000005dafb80800\
+        0000000000000000000000000000000000000000000000000000005dafbbf003\
+        00000000000000000000000063773230746f6b656e0000000000000000000000\
+        000300000000000000000de0b6b3a76400000100000000000000000000000000\
+        00000000000000000000000000756c756e61000300000000000000000de0b6b3\
+        a7640000000000000000000000000000ddb64fe46a91d46ee29420539fc25fd0\
+        7c5fea3e0002000000000000000002c68af0bb14000000000000000000000000\
+        000022d491bde2303f2f43325b2108d26f1eaba1e32b1df62f291b2e969fb084\
+        9d99d9ce41e2f137006e00000000000000000000000000000000000000000000\
+        0000000000005dafbfd8";
+    let signed_vaa = hex::decode(signed_vaa).unwrap();
+
+    execute(
+        deps,
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::InitSale {
+            data: Binary::from(signed_vaa),
+        },
+    )?;
+
+    Ok(())
+}
+
+fn query_total_contribution(deps: Deps, token_index: u8) -> Result<Uint256, ContractError> {
+    let response = query(
+        deps,
+        mock_env(),
+        QueryMsg::TotalContribution {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+            token_index,
+        },
+    )?;
+    let total_contribution: TotalContributionResponse = from_binary(&response)?;
+    Ok(total_contribution.amount)
+}
+
+#[test]
+fn contribute_cw20() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    let signature = "\
+        64d244dc938346fc274cf69af803567e7661d6f579ca0f2cf05545e4d24df4ea\
+        3cfb6818d42a178e8aa8fe513376b985a34b57e83a71a62d9f9af31d10da99f8\
+        00";
+    let signature = hex::decode(signature).unwrap();
+
+    let msg = ExecuteMsg::Contribute {
+        sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        token_index: 0,
+        amount: Uint256::from(1_000_000u128),
+        signature: Binary::from(signature),
+    };
+    let response = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg)?;
+
+    // contribution is pulled from the buyer with a CW20 TransferFrom
+    assert_eq!(response.messages.len(), 1, "response.messages.len() != 1");
+    let expected = SubMsg::reply_on_success(
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "cw20token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "addr0001".to_string(),
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(1_000_000u128),
+            })?,
+            funds: vec![],
+        }),
+        CONTRIBUTE_REPLY_ID,
+    );
+    assert_eq!(response.messages[0], expected);
+
+    // nothing is credited until the transfer completes
+    assert_eq!(
+        query_total_contribution(deps.as_ref(), 0)?,
+        Uint256::zero(),
+        "total_contribution.amount != 0"
+    );
+
+    receive_cw20_contribution(&mut deps, 1_000_000)?;
+    assert_eq!(
+        query_total_contribution(deps.as_ref(), 0)?,
+        Uint256::from(1_000_000u128),
+        "total_contribution.amount != expected"
+    );
+
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BuyerStatus {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+            token_index: 0,
+            buyer: "addr0001".to_string(),
+        },
+    )?;
+    let buyer_status: BuyerStatusResponse = from_binary(&response)?;
+    assert_eq!(
+        buyer_status.contribution,
+        Uint256::from(1_000_000u128),
+        "buyer_status.contribution != expected"
+    );
+    assert_eq!(
+        buyer_status.allocation_is_claimed, false,
+        "buyer_status.allocation_is_claimed is true"
+    );
+    assert_eq!(
+        buyer_status.refund_is_claimed, false,
+        "buyer_status.refund_is_claimed is true"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn contribute_cw20_fee_on_transfer() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    let signature = "\
+        64d244dc938346fc274cf69af803567e7661d6f579ca0f2cf05545e4d24df4ea\
+        3cfb6818d42a178e8aa8fe513376b985a34b57e83a71a62d9f9af31d10da99f8\
+        00";
+    let msg = ExecuteMsg::Contribute {
+        sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        token_index: 0,
+        amount: Uint256::from(1_000_000u128),
+        signature: Binary::from(hex::decode(signature).unwrap()),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg)?;
+
+    // the token took a 1% fee on the way in, so the buyer only gets credited
+    // with what the contract actually received
+    let response = receive_cw20_contribution(&mut deps, 990_000)?;
+    assert!(
+        response
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "received" && attr.value == "990000"),
+        "received attribute != 990000"
+    );
+    assert_eq!(
+        query_total_contribution(deps.as_ref(), 0)?,
+        Uint256::from(990_000u128),
+        "total_contribution.amount != expected"
+    );
+
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BuyerStatus {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+            token_index: 0,
+            buyer: "addr0001".to_string(),
+        },
+    )?;
+    let buyer_status: BuyerStatusResponse = from_binary(&response)?;
+    assert_eq!(
+        buyer_status.contribution,
+        Uint256::from(990_000u128),
+        "buyer_status.contribution != expected"
+    );
+
+    // there is nothing left to credit once the reply has been handled
+    let result = receive_cw20_contribution(&mut deps, 990_000);
+    assert!(result.is_err(), "contribution credited twice");
+
+    Ok(())
+}
+
+#[test]
+fn contribute_cw20_capped_at_signed_amount() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    let signature = "\
+        64d244dc938346fc274cf69af803567e7661d6f579ca0f2cf05545e4d24df4ea\
+        3cfb6818d42a178e8aa8fe513376b985a34b57e83a71a62d9f9af31d10da99f8\
+        00";
+    let msg = ExecuteMsg::Contribute {
+        sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        token_index: 0,
+        amount: Uint256::from(1_000_000u128),
+        signature: Binary::from(hex::decode(signature).unwrap()),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg)?;
+
+    // more arrived than was requested, but the kyc authority only signed off
+    // on 1_000_000
+    let response = receive_cw20_contribution(&mut deps, 1_010_000)?;
+    assert!(
+        response
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "credited" && attr.value == "1000000"),
+        "credited attribute != 1000000"
+    );
+    assert_eq!(
+        query_total_contribution(deps.as_ref(), 0)?,
+        Uint256::from(1_000_000u128),
+        "total_contribution.amount != expected"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn contribute_native() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    let signature = "\
+        bb82794360fa8b37fb81e6f41ba78cd9877d7e90b2dafc745a651dbd7e5ab440\
+        3c31315f0a8d568e08e257eb85ea6ad62fec247a2bb00c7fc3c72ed259da954e\
+        01";
+    let signature = hex::decode(signature).unwrap();
+
+    let msg = ExecuteMsg::Contribute {
+        sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        token_index: 1,
+        amount: Uint256::from(2_000_000u128),
+        signature: Binary::from(signature),
+    };
+
+    // funds must match the contributed amount
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &coins(1_000_000, "uluna")),
+        msg.clone(),
+    );
+    assert!(result.is_err(), "contribute succeeded with wrong funds");
+
+    let response = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &coins(2_000_000, "uluna")),
+        msg,
+    )?;
+    assert_eq!(response.messages.len(), 0, "response.messages.len() != 0");
+
+    assert_eq!(
+        query_total_contribution(deps.as_ref(), 1)?,
+        Uint256::from(2_000_000u128),
+        "total_contribution.amount != expected"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn contribute_invalid_kyc_signature() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    // signature was produced for 1_000_000, not 1_000_001
+    let signature = "\
+        64d244dc938346fc274cf69af803567e7661d6f579ca0f2cf05545e4d24df4ea\
+        3cfb6818d42a178e8aa8fe513376b985a34b57e83a71a62d9f9af31d10da99f8\
+        00";
+    let signature = hex::decode(signature).unwrap();
+
+    let msg = ExecuteMsg::Contribute {
+        sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        token_index: 0,
+        amount: Uint256::from(1_000_001u128),
+        signature: Binary::from(signature.clone()),
+    };
+    let result = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg);
+    assert!(result.is_err(), "contribute succeeded with bad amount");
+
+    // nor can another buyer reuse it
+    let msg = ExecuteMsg::Contribute {
+        sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        token_index: 0,
+        amount: Uint256::from(1_000_000u128),
+        signature: Binary::from(signature),
+    };
+    let result = execute(deps.as_mut(), mock_env(), mock_info("addr0002", &[]), msg);
+    assert!(result.is_err(), "contribute succeeded with other buyer");
+
+    assert_eq!(
+        query_total_contribution(deps.as_ref(), 0)?,
+        Uint256::zero(),
+        "total_contribution.amount != 0"
+    );
+
+    // rejected contributions leave no buyer record behind
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BuyerStatus {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+            token_index: 0,
+            buyer: "addr0001".to_string(),
+        },
+    );
+    assert!(response.is_err(), "buyer_status found for rejected buyer");
+
+    Ok(())
+}
+
+// sale 2 accepts a cw20 that does not exist (index 0) and uluna (index 1)
+const INVALID_TOKEN_SALE_VAA: &str = "\
+    01000000000100fd75a4cd9fe22519fbe7856267f59d0e0bf9931bf8a7733de4\
+    e7b576451ae1b3309c9adee5c96e82e9b06b03fa16e189e3fe9eff132c1f9ce9\
+    22710524a6476300000003880000000000020000000000000000000000005f8e\
+    26facc23fa4cbd87b8d9dbbd33d5047abde100000000000000050f0100000000\
+    0000000000000000000000000000000000000000000000000000000200000000\
+    000000000000000083752ecafebf4707258dedffbd9c7443148169db00021200\
+    0000000000000000000000000000000000000000000000000000005dafb80800\
+    0000000000000000000000000000000000000000000000000000005dafbbf002\
+    0000000000000000000000006e6f746163773230000000000000000000000000\
+    000300000000000000000de0b6b3a76400000100000000000000000000000000\
+    00000000000000000000000000756c756e61000300000000000000000de0b6b3\
+    a764000000000000000000000000000022d491bde2303f2f43325b2108d26f1e\
+    aba1e32b1df62f291b2e969fb0849d99d9ce41e2f137006e0000000000000000\
+    00000000000000000000000000000000000000005dafbfd8";
+
+#[test]
+fn contribute_invalid_accepted_token() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    // the bad token must not prevent the sale from being initialized
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::InitSale {
+            data: Binary::from(hex::decode(INVALID_TOKEN_SALE_VAA).unwrap()),
+        },
+    )?;
+
+    let mut sale_id = [0u8; 32];
+    sale_id[31] = 2;
+
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AcceptedToken {
+            sale_id: Binary::from(&sale_id[..]),
+            token_index: 0,
+        },
+    )?;
+    let token: AcceptedTokenResponse = from_binary(&response)?;
+    assert_eq!(
+        &token.address[12..20],
+        MOCK_INVALID_CW20_ADDR.as_bytes(),
+        "token.address != expected"
+    );
+
+    // the invalid token is rejected before the signature is even looked at
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::Contribute {
+            sale_id: Binary::from(&sale_id[..]),
+            token_index: 0,
+            amount: Uint256::from(1_000_000u128),
+            signature: Binary::from(vec![0u8; 65]),
+        },
+    );
+    assert_eq!(
+        result,
+        Err(ContractError::InvalidAcceptedToken {
+            sale_id: hex::encode(sale_id),
+            token_index: 0,
+        }),
+        "contribute succeeded with invalid token"
+    );
+
+    // while the valid one still works
+    let signature = "\
+        09d60d35dff84f648895d537a0307bd198eaf2b0190b70ca6dfdefdfd8bec4c4\
+        416d39ae4e68a27d998c36ae21c7f8aecdfd215bbca51e8f83c4024664b907b9\
+        01";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &coins(2_000_000, "uluna")),
+        ExecuteMsg::Contribute {
+            sale_id: Binary::from(&sale_id[..]),
+            token_index: 1,
+            amount: Uint256::from(2_000_000u128),
+            signature: Binary::from(hex::decode(signature).unwrap()),
+        },
+    )?;
+
+    Ok(())
+}
+
+#[test]
+fn contribute_unknown_native_denom() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    // same sale, but index 1 is "ufake" instead of "uluna"
+    let signed_vaa = INVALID_TOKEN_SALE_VAA.replace("756c756e61", "7566616b65");
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::InitSale {
+            data: Binary::from(hex::decode(signed_vaa).unwrap()),
+        },
+    )?;
+
+    let mut sale_id = [0u8; 32];
+    sale_id[31] = 2;
+
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &coins(2_000_000, "ufake")),
+        ExecuteMsg::Contribute {
+            sale_id: Binary::from(&sale_id[..]),
+            token_index: 1,
+            amount: Uint256::from(2_000_000u128),
+            signature: Binary::from(vec![0u8; 65]),
+        },
+    );
+    assert_eq!(
+        result,
+        Err(ContractError::InvalidAcceptedToken {
+            sale_id: hex::encode(sale_id),
+            token_index: 1,
+        }),
+        "contribute succeeded with unknown denom"
+    );
+
+    Ok(())
+}
+
+// the mock querier reports the same cw20 balance for every account, so it is
+// bumped to what the contract would hold once the transfer went through
+fn receive_cw20_contribution(
+    deps: &mut OwnedDeps<MockStorage, TerraMockApi, WasmMockQuerier>,
+    balance: u128,
+) -> Result<Response, ContractError> {
+    deps.querier.with_cw20_balance(Uint128::from(balance));
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: CONTRIBUTE_REPLY_ID,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+}
+
+// addr0001 contributes 1_000_000 of the cw20 (index 0) and 2_000_000 uluna
+// (index 1) to sale 1
+fn contribute_to_sale(
+    deps: &mut OwnedDeps<MockStorage, TerraMockApi, WasmMockQuerier>,
+) -> Result<(), ContractError> {
+    let signature = "\
+        64d244dc938346fc274cf69af803567e7661d6f579ca0f2cf05545e4d24df4ea\
+        3cfb6818d42a178e8aa8fe513376b985a34b57e83a71a62d9f9af31d10da99f8\
+        00";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::Contribute {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+            token_index: 0,
+            amount: Uint256::from(1_000_000u128),
+            signature: Binary::from(hex::decode(signature).unwrap()),
+        },
+    )?;
+    receive_cw20_contribution(deps, 1_000_000)?;
+
+    let signature = "\
+        bb82794360fa8b37fb81e6f41ba78cd9877d7e90b2dafc745a651dbd7e5ab440\
+        3c31315f0a8d568e08e257eb85ea6ad62fec247a2bb00c7fc3c72ed259da954e\
+        01";
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &coins(2_000_000, "uluna")),
+        ExecuteMsg::Contribute {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+            token_index: 1,
+            amount: Uint256::from(2_000_000u128),
+            signature: Binary::from(hex::decode(signature).unwrap()),
+        },
+    )?;
+
+    Ok(())
+}
+
+fn seal_contribute_sale(deps: DepsMut) -> Result<Response, ContractError> {
+    /* sale sealed payload
+      saleId: 1
+      allocations: [
+        { tokenIndex: 0, allocation: 5000000000000000000, excessContribution: 100000 },
+        { tokenIndex: 1, allocation: 3000000000000000000, excessContribution: 500000 },
+        { tokenIndex: 2, allocation: 0, excessContribution: 0 },
+      ]
+    */
+    let signed_vaa = "\
+        01000000000100fd75a4cd9fe22519fbe7856267f59d0e0bf9931bf8a7733de4\
+        e7b576451ae1b3309c9adee5c96e82e9b06b03fa16e189e3fe9eff132c1f9ce9\
+        22710524a6476300000003880000000000020000000000000000000000005f8e\
+        26facc23fa4cbd87b8d9dbbd33d5047abde100000000000000020f0300000000\
+        0000000000000000000000000000000000000000000000000000000103000000\
+        000000000000000000000000000000000000000000004563918244f400000000\
+        0000000000000000000000000000000000000000000000000000000186a00100\
+        000000000000000000000000000000000000000000000029a2241af62c000000\
+        0000000000000000000000000000000000000000000000000000000007a12002\
+        0000000000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000";
+    let signed_vaa = hex::decode(signed_vaa).unwrap();
+    execute(
+        deps,
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::SaleSealed {
+            data: Binary::from(signed_vaa),
+        },
+    )
+}
+
+#[test]
+fn sale_sealed_bridges_contributions() -> Result<(), ContractError> {
+    // the conductor has not sent enough of the sale token yet. the wrapped
+    // token has 8 decimals, so 800_000_000 covers both local allocations.
+    // mock storage is not reverted on error, so the failed seal gets its own
+    // deps (otherwise the vaa would already be consumed)
+    let mut underfunded = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(underfunded.as_mut())?;
+    contribute_to_sale(&mut underfunded)?;
+    underfunded
+        .querier
+        .with_cw20_balance(Uint128::from(799_999_999u128));
+    let result = seal_contribute_sale(underfunded.as_mut());
+    assert_eq!(
+        result,
+        Err(ContractError::InsufficientFunds {
+            sale_id: hex::encode(CONTRIBUTE_SALE_ID),
+        }),
+        "sale_sealed succeeded with insufficient funds"
+    );
+
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+    contribute_to_sale(&mut deps)?;
+    deps.querier
+        .with_cw20_balance(Uint128::from(800_000_000u128));
+    let response = seal_contribute_sale(deps.as_mut())?;
+
+    // contributions minus excess go to the sale recipient on the conductor chain
+    let recipient = "00000000000000000000000022d491bde2303f2f43325b2108d26f1eaba1e32b";
+    let recipient = Binary::from(hex::decode(recipient).unwrap());
+
+    let expected = vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "cw20token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                spender: MOCK_TOKEN_BRIDGE_ADDR.to_string(),
+                amount: Uint128::from(900_000u128),
+                expires: None,
+            })?,
+            funds: vec![],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_TOKEN_BRIDGE_ADDR.to_string(),
+            msg: to_binary(&TokenBridgeExecuteMsg::InitiateTransfer {
+                asset: Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "cw20token".to_string(),
+                    },
+                    amount: Uint128::from(900_000u128),
+                },
+                recipient_chain: 2,
+                recipient: recipient.clone(),
+                fee: Uint128::zero(),
+                nonce: 0,
+            })?,
+            funds: vec![],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_TOKEN_BRIDGE_ADDR.to_string(),
+            msg: to_binary(&TokenBridgeExecuteMsg::DepositTokens {})?,
+            funds: coins(1_500_000, "uluna"),
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_TOKEN_BRIDGE_ADDR.to_string(),
+            msg: to_binary(&TokenBridgeExecuteMsg::InitiateTransfer {
+                asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uluna".to_string(),
+                    },
+                    amount: Uint128::from(1_500_000u128),
+                },
+                recipient_chain: 2,
+                recipient,
+                fee: Uint128::zero(),
+                nonce: 0,
+            })?,
+            funds: vec![],
+        }),
+    ];
+    let messages: Vec<CosmosMsg> = response.messages.into_iter().map(|msg| msg.msg).collect();
+    assert_eq!(messages, expected);
+
+    // and the sale cannot be sealed twice
+    let result = seal_contribute_sale(deps.as_mut());
+    assert_eq!(
+        result,
+        Err(ContractError::VaaAlreadyExecuted),
+        "sale_sealed succeeded twice"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn claim_allocation() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+    contribute_to_sale(&mut deps)?;
+
+    let claim_msg = |token_index: u8| ExecuteMsg::ClaimAllocation {
+        sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        token_index,
+    };
+
+    // nothing to claim until the sale is sealed
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        claim_msg(0),
+    );
+    assert!(result.is_err(), "claim_allocation succeeded before seal");
+
+    deps.querier
+        .with_cw20_balance(Uint128::from(800_000_000u128));
+    seal_contribute_sale(deps.as_mut())?;
+
+    // allocations are locked until 1571799000
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        claim_msg(0),
+    );
+    assert_eq!(
+        result,
+        Err(ContractError::AllocationsLocked {
+            sale_id: hex::encode(CONTRIBUTE_SALE_ID),
+        }),
+        "claim_allocation succeeded while locked"
+    );
+
+    let mut unlocked_env = mock_env();
+    unlocked_env.block.time = Timestamp::from_seconds(1_571_799_000);
+
+    // the buyer is the only contributor, so they get everything. the sale token
+    // has 18 decimals, but its wrapped cw20 only has 8
+    let response = execute(
+        deps.as_mut(),
+        unlocked_env.clone(),
+        mock_info("addr0001", &[]),
+        claim_msg(0),
+    )?;
+    assert_eq!(response.messages.len(), 2, "response.messages.len() != 2");
+    assert_eq!(
+        response.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_WRAPPED_ADDR.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(500_000_000u128),
+            })?,
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        response.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "cw20token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(100_000u128),
+            })?,
+            funds: vec![],
+        })
+    );
+
+    // cannot claim twice
+    let result = execute(
+        deps.as_mut(),
+        unlocked_env.clone(),
+        mock_info("addr0001", &[]),
+        claim_msg(0),
+    );
+    assert!(result.is_err(), "claim_allocation succeeded twice");
+
+    // excess native contributions are sent back through the bank module
+    let response = execute(
+        deps.as_mut(),
+        unlocked_env.clone(),
+        mock_info("addr0001", &[]),
+        claim_msg(1),
+    )?;
+    assert_eq!(response.messages.len(), 2, "response.messages.len() != 2");
+    assert_eq!(
+        response.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_WRAPPED_ADDR.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(300_000_000u128),
+            })?,
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        response.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: coins(500_000, "uluna"),
+        })
+    );
+
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BuyerStatus {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+            token_index: 1,
+            buyer: "addr0001".to_string(),
+        },
+    )?;
+    let buyer_status: BuyerStatusResponse = from_binary(&response)?;
+    assert_eq!(
+        buyer_status.allocation_is_claimed, true,
+        "buyer_status.allocation_is_claimed is false"
+    );
+
+    // another buyer has nothing to claim
+    let result = execute(
+        deps.as_mut(),
+        unlocked_env.clone(),
+        mock_info("addr0002", &[]),
+        claim_msg(0),
+    );
+    assert!(result.is_err(), "claim_allocation succeeded for non-buyer");
+
+    Ok(())
+}
+
+#[test]
+fn claim_refund() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+    contribute_to_sale(&mut deps)?;
+
+    let claim_msg = |token_index: u8| ExecuteMsg::ClaimRefund {
+        sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        token_index,
+    };
+
+    // refunds are only available for aborted sales
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        claim_msg(0),
+    );
+    assert!(result.is_err(), "claim_refund succeeded before abort");
+
+    let signed_vaa = "\
+        01000000000100fd75a4cd9fe22519fbe7856267f59d0e0bf9931bf8a7733de4\
+        e7b576451ae1b3309c9adee5c96e82e9b06b03fa16e189e3fe9eff132c1f9ce9\
+        22710524a6476300000003880000000000020000000000000000000000005f8e\
+        26facc23fa4cbd87b8d9dbbd33d5047abde100000000000000030f0400000000\
+        00000000000000000000000000000000000000000000000000000001";
+    let signed_vaa = hex::decode(signed_vaa).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::SaleAborted {
+            data: Binary::from(signed_vaa),
+        },
+    )?;
+
+    let response = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        claim_msg(0),
+    )?;
+    assert_eq!(response.messages.len(), 1, "response.messages.len() != 1");
+    assert_eq!(
+        response.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "cw20token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(1_000_000u128),
+            })?,
+            funds: vec![],
+        })
+    );
+
+    // cannot claim twice
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        claim_msg(0),
+    );
+    assert!(result.is_err(), "claim_refund succeeded twice");
+
+    let response = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        claim_msg(1),
+    )?;
+    assert_eq!(response.messages.len(), 1, "response.messages.len() != 1");
+    assert_eq!(
+        response.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: coins(2_000_000, "uluna"),
+        })
+    );
+
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BuyerStatus {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+            token_index: 1,
+            buyer: "addr0001".to_string(),
+        },
+    )?;
+    let buyer_status: BuyerStatusResponse = from_binary(&response)?;
+    assert_eq!(
+        buyer_status.refund_is_claimed, true,
+        "buyer_status.refund_is_claimed is false"
+    );
+
+    // the buyer never contributed the foreign token
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        claim_msg(2),
+    );
+    assert!(
+        result.is_err(),
+        "claim_refund succeeded without contribution"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn sale_aborted_after_sealed() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+    contribute_to_sale(&mut deps)?;
+
+    deps.querier
+        .with_cw20_balance(Uint128::from(800_000_000u128));
+    seal_contribute_sale(deps.as_mut())?;
+
+    // a distinct abort vaa for the same sale
+    let signed_vaa = "\
+        01000000000100fd75a4cd9fe22519fbe7856267f59d0e0bf9931bf8a7733de4\
+        e7b576451ae1b3309c9adee5c96e82e9b06b03fa16e189e3fe9eff132c1f9ce9\
+        22710524a6476300000003880000000000020000000000000000000000005f8e\
+        26facc23fa4cbd87b8d9dbbd33d5047abde100000000000000030f0400000000\
+        00000000000000000000000000000000000000000000000000000001";
+    let signed_vaa = hex::decode(signed_vaa).unwrap();
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::SaleAborted {
+            data: Binary::from(signed_vaa),
+        },
+    );
+    assert_eq!(
+        result,
+        Err(ContractError::SaleAlreadySealedOrAborted {
+            sale_id: hex::encode(CONTRIBUTE_SALE_ID),
+        }),
+        "sealed sale was aborted"
+    );
+
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SaleStatus {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        },
+    )?;
+    let sale_status: SaleStatusResponse = from_binary(&response)?;
+    assert_eq!(
+        sale_status.is_sealed, true,
+        "sale_status.is_sealed is false"
+    );
+    assert_eq!(
+        sale_status.is_aborted, false,
+        "sale_status.is_aborted is true"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn attest_contributions_unknown_sale() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    let sale_id = [0xffu8; 32];
+    let result = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::AttestContributions {
+            sale_id: Binary::from(&sale_id[..]),
+        },
+    );
+    assert_eq!(
+        result,
+        Err(ContractError::SaleNotFound {
+            sale_id: hex::encode(sale_id),
+        }),
+        "attest_contributions found unknown sale"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn kyc_authority_updated() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    // rotate the kyc authority to befa429d57cd18b7f8a4d91a2da9ab4af05d0fbe
+    let signed_vaa = "\
+        01000000000100fd75a4cd9fe22519fbe7856267f59d0e0bf9931bf8a7733de4\
+        e7b576451ae1b3309c9adee5c96e82e9b06b03fa16e189e3fe9eff132c1f9ce9\
+        22710524a6476300000003880000000000020000000000000000000000005f8e\
+        26facc23fa4cbd87b8d9dbbd33d5047abde100000000000000040f0600000000\
+        00000000000000000000000000000000000000000000000000000001befa429d\
+        57cd18b7f8a4d91a2da9ab4af05d0fbe";
+    let signed_vaa = hex::decode(signed_vaa).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::KycAuthorityUpdated {
+            data: Binary::from(signed_vaa),
+        },
+    )?;
+
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SaleRegistry {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        },
+    )?;
+    let sale: SaleRegistryResponse = from_binary(&response)?;
+    let authority = hex::decode("befa429d57cd18b7f8a4d91a2da9ab4af05d0fbe").unwrap();
+    assert_eq!(&sale.authority, &authority, "sale.authority != expected");
+
+    // signatures from the old authority are no longer accepted
+    let signature = "\
+        64d244dc938346fc274cf69af803567e7661d6f579ca0f2cf05545e4d24df4ea\
+        3cfb6818d42a178e8aa8fe513376b985a34b57e83a71a62d9f9af31d10da99f8\
+        00";
+    let msg = ExecuteMsg::Contribute {
+        sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        token_index: 0,
+        amount: Uint256::from(1_000_000u128),
+        signature: Binary::from(hex::decode(signature).unwrap()),
+    };
+    let result = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg);
+    assert!(result.is_err(), "contribute succeeded with old authority");
+
+    // but the new one's are
+    let signature = "\
+        b0d4bc5bb4df69283bcb65efdd0881909f441c037cc15b3e4a210b84be1f5fc2\
+        1fc25ca005ccc345983c1134be9d41d2bc9a242377251564c2ed730b2a1c72ff\
+        01";
+    let msg = ExecuteMsg::Contribute {
+        sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        token_index: 0,
+        amount: Uint256::from(1_000_000u128),
+        signature: Binary::from(hex::decode(signature).unwrap()),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg)?;
+    receive_cw20_contribution(&mut deps, 1_000_000)?;
+
+    assert_eq!(
+        query_total_contribution(deps.as_ref(), 0)?,
+        Uint256::from(1_000_000u128),
+        "total_contribution.amount != expected"
+    );
+
+    Ok(())
+}
+
+fn submit_governance_vaa(deps: DepsMut, signed_vaa: &str) -> Result<Response, ContractError> {
+    let signed_vaa = hex::decode(signed_vaa).unwrap();
+    execute(
+        deps,
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::SubmitVaa {
+            data: Binary::from(signed_vaa),
+        },
+    )
+}
+
+#[test]
+fn governance_upgrade_contract() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    // module "TokenSaleContributor", action 1, chain 3, new code id 42
+    let signed_vaa = "\
+        01000000000100fd75a4cd9fe22519fbe7856267f59d0e0bf9931bf8a7733de4\
+        e7b576451ae1b3309c9adee5c96e82e9b06b03fa16e189e3fe9eff132c1f9ce9\
+        22710524a6476300000003880000000000010000000000000000000000000000\
+        00000000000000000000000000000000000000000000000000000f0000000000\
+        00000000000000546f6b656e53616c65436f6e7472696275746f720100030000\
+        00000000000000000000000000000000000000000000000000000000002a";
+    let response = submit_governance_vaa(deps.as_mut(), signed_vaa)?;
+    assert_eq!(
+        response.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Migrate {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            new_code_id: 42,
+            msg: to_binary(&MigrateMsg {})?,
+        }),
+        "response.messages[0] != expected"
+    );
+
+    // the same vaa cannot be executed twice
+    let result = submit_governance_vaa(deps.as_mut(), signed_vaa);
+    assert_eq!(
+        result,
+        Err(ContractError::VaaAlreadyExecuted),
+        "replayed vaa was executed"
+    );
+
+    // upgrades cannot target every chain (chain 0)
+    let signed_vaa = "\
+        01000000000100fd75a4cd9fe22519fbe7856267f59d0e0bf9931bf8a7733de4\
+        e7b576451ae1b3309c9adee5c96e82e9b06b03fa16e189e3fe9eff132c1f9ce9\
+        22710524a6476300000003880000000000010000000000000000000000000000\
+        00000000000000000000000000000000000000000000000000020f0000000000\
+        00000000000000546f6b656e53616c65436f6e7472696275746f720100000000\
+        00000000000000000000000000000000000000000000000000000000002a";
+    let result = submit_governance_vaa(deps.as_mut(), signed_vaa);
+    assert_eq!(
+        result,
+        Err(ContractError::WrongChain {
+            expected: 3,
+            actual: 0,
+        }),
+        "upgrade for chain 0 was executed"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn governance_update_conductor() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    // module "TokenSaleContributor", action 2, chain 0, conductor on chain 6
+    let signed_vaa = "\
+        01000000000100fd75a4cd9fe22519fbe7856267f59d0e0bf9931bf8a7733de4\
+        e7b576451ae1b3309c9adee5c96e82e9b06b03fa16e189e3fe9eff132c1f9ce9\
+        22710524a6476300000003880000000000010000000000000000000000000000\
+        00000000000000000000000000000000000000000000000000010f0000000000\
+        00000000000000546f6b656e53616c65436f6e7472696275746f720200000006\
+        000000000000000000000000e1bd7a04d533bddbd9b887bd4cfa23ccfa268e5f";
+    submit_governance_vaa(deps.as_mut(), signed_vaa)?;
+
+    let response = query(deps.as_ref(), mock_env(), QueryMsg::Config {})?;
+    let config: ConfigResponse = from_binary(&response)?;
+    let conductor_address =
+        hex::decode("000000000000000000000000e1bd7a04d533bddbd9b887bd4cfa23ccfa268e5f").unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            conductor_chain: 6,
+            conductor_address,
+        },
+        "config != ConfigResponse"
+    );
+
+    // vaas from the old conductor are no longer accepted
+    let result = seal_contribute_sale(deps.as_mut());
+    assert!(result.is_err(), "sale sealed by old conductor");
+
+    Ok(())
+}
+
+#[test]
+fn governance_invalid_emitter() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+
+    // a valid upgrade payload, but emitted by the conductor
+    let signed_vaa = "\
+        01000000000100fd75a4cd9fe22519fbe7856267f59d0e0bf9931bf8a7733de4\
+        e7b576451ae1b3309c9adee5c96e82e9b06b03fa16e189e3fe9eff132c1f9ce9\
+        22710524a6476300000003880000000000020000000000000000000000005f8e\
+        26facc23fa4cbd87b8d9dbbd33d5047abde100000000000000000f0000000000\
+        00000000000000546f6b656e53616c65436f6e7472696275746f720100030000\
+        00000000000000000000000000000000000000000000000000000000002a";
+    let result = submit_governance_vaa(deps.as_mut(), signed_vaa);
+    assert_eq!(
+        result,
+        Err(ContractError::InvalidEmitter {
+            expected_chain: 1,
+            expected_address: hex::encode(ADDRESS_ZERO),
+            actual_chain: 2,
+            actual_address: "0000000000000000000000005f8e26facc23fa4cbd87b8d9dbbd33d5047abde1"
+                .to_string(),
+        }),
+        "governance vaa from conductor was executed"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn list_queries() -> Result<(), ContractError> {
+    let mut deps = mock_dependencies(&[]);
+    instantiate_and_init_contribute_sale(deps.as_mut())?;
+    contribute_to_sale(&mut deps)?;
+
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AllSales {
+            start_after: None,
+            limit: None,
+        },
+    )?;
+    let all_sales: AllSalesResponse = from_binary(&response)?;
+    assert_eq!(all_sales.sales.len(), 1, "all_sales.sales.len() != 1");
+    assert_eq!(
+        &all_sales.sales[0].id[..],
+        &CONTRIBUTE_SALE_ID[..],
+        "all_sales.sales[0].id != expected"
+    );
+
+    // nothing comes after the only sale
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AllSales {
+            start_after: Some(Binary::from(&CONTRIBUTE_SALE_ID[..])),
+            limit: None,
+        },
+    )?;
+    let all_sales: AllSalesResponse = from_binary(&response)?;
+    assert!(all_sales.sales.is_empty(), "all_sales.sales not empty");
+
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AcceptedTokens {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+        },
+    )?;
+    let accepted_tokens: AcceptedTokensResponse = from_binary(&response)?;
+    let totals: Vec<(u8, u16, Uint256)> = accepted_tokens
+        .tokens
+        .iter()
+        .map(|token| (token.token_index, token.chain, token.total_contribution))
+        .collect();
+    assert_eq!(
+        totals,
+        vec![
+            (0, 3, Uint256::from(1_000_000u128)),
+            (1, 3, Uint256::from(2_000_000u128)),
+            (2, 2, Uint256::zero()),
+        ],
+        "accepted_tokens != expected"
+    );
+
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BuyerContributions {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+            buyer: "addr0001".to_string(),
+        },
+    )?;
+    let buyer_contributions: BuyerContributionsResponse = from_binary(&response)?;
+    assert_eq!(
+        buyer_contributions.contributions,
+        vec![
+            BuyerContribution {
+                token_index: 0,
+                contribution: Uint256::from(1_000_000u128),
+                allocation_is_claimed: false,
+                refund_is_claimed: false,
+            },
+            BuyerContribution {
+                token_index: 1,
+                contribution: Uint256::from(2_000_000u128),
+                allocation_is_claimed: false,
+                refund_is_claimed: false,
+            },
+        ],
+        "buyer_contributions != expected"
+    );
+
+    // someone who never contributed has nothing to show
+    let response = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BuyerContributions {
+            sale_id: Binary::from(&CONTRIBUTE_SALE_ID[..]),
+            buyer: "addr0002".to_string(),
+        },
+    )?;
+    let buyer_contributions: BuyerContributionsResponse = from_binary(&response)?;
+    assert!(
+        buyer_contributions.contributions.is_empty(),
+        "buyer_contributions not empty"
+    );
+
     Ok(())
 }